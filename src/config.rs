@@ -0,0 +1,177 @@
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+use crate::display::DisplayFormats;
+use crate::queries::{add_default_queries, add_unfinished_queries, QUERIES};
+use crate::query::{AQuery, Maturity, Severity};
+use crate::select::Selection;
+
+pub const CONFIG_FILE_NAME: &str = "nixpkgs-lint.toml";
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    /// also enables unfinished lints
+    pub enabled: Option<bool>,
+    pub severity: Option<Severity>,
+}
+
+/// The contents of a `nixpkgs-lint.toml`, for example:
+///
+/// ```toml
+/// format = "json"
+/// exclude = ["pkgs/test/**"]
+///
+/// [rules.FlagsNotList]
+/// enabled = false
+///
+/// [rules.SRIHash]
+/// enabled = true
+/// severity = "error"
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// used when `--format` isn't passed
+    pub format: Option<DisplayFormats>,
+    /// globs relative to the directory of the config file
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// by lint id
+    #[serde(default)]
+    pub rules: HashMap<String, RuleConfig>,
+    /// the directory of the config file
+    #[serde(skip)]
+    pub dir: PathBuf,
+    #[serde(skip)]
+    exclude_set: GlobSet,
+}
+
+impl Config {
+    /// Finds the config file in `path` or the closest of its parent directories.
+    pub fn discover(path: &Path) -> Result<Option<Config>, String> {
+        let path = path
+            .canonicalize()
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+
+        match path
+            .ancestors()
+            .find(|dir| dir.join(CONFIG_FILE_NAME).is_file())
+        {
+            Some(dir) => Config::load(dir).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn load(dir: &Path) -> Result<Config, String> {
+        let file = dir.join(CONFIG_FILE_NAME);
+        read_to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| Config::parse(&contents, dir))
+            .map_err(|e| format!("{}: {e}", file.display()))
+    }
+
+    fn parse(contents: &str, dir: &Path) -> Result<Config, String> {
+        let mut config: Config = toml::from_str(contents).map_err(|e| e.to_string())?;
+
+        for id in config.rules.keys() {
+            if !QUERIES.contains_key(id) {
+                return Err(format!("unknown lint '{id}'"));
+            }
+        }
+
+        let mut exclude_set = GlobSetBuilder::new();
+        for glob in &config.exclude {
+            exclude_set.add(Glob::new(glob).map_err(|e| e.to_string())?);
+        }
+        config.exclude_set = exclude_set.build().map_err(|e| e.to_string())?;
+        config.dir = dir.to_owned();
+
+        Ok(config)
+    }
+
+    /// The lints to run, with the severities of the config.
+    pub fn queries(&self, include_unfinished_lints: bool, selection: &Selection) -> Vec<AQuery> {
+        let mut queries: Vec<AQuery> = Vec::new();
+
+        add_default_queries(&mut queries);
+        add_unfinished_queries(&mut queries);
+
+        queries
+            .into_iter()
+            .filter_map(|mut q| {
+                let rule = self.rules.get(&q.id);
+                let enabled = rule.and_then(|r| r.enabled);
+                let default = q.maturity == Maturity::Stable || include_unfinished_lints;
+                if let Some(severity) = rule.and_then(|r| r.severity) {
+                    q.severity = severity;
+                }
+                selection.is_selected(&q, enabled, default).then_some(q)
+            })
+            .collect()
+    }
+
+    /// Whether `file` matches one of the exclude globs, `file` is an absolute path.
+    pub fn is_excluded(&self, file: &Path) -> bool {
+        file.strip_prefix(&self.dir)
+            .is_ok_and(|relative| self.exclude_set.is_match(relative))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::Config;
+    use crate::{query::Severity, select::Selection};
+
+    fn config(toml: &str) -> Config {
+        Config::parse(toml, Path::new("/nixpkgs")).unwrap()
+    }
+
+    #[test]
+    fn config_rules() {
+        let config = config(
+            r#"
+            [rules.FlagsNotList]
+            enabled = false
+
+            [rules.SRIHash]
+            enabled = true
+            severity = "error"
+
+            [rules.ArgsToOptionalIsList]
+            severity = "hint"
+            "#,
+        );
+        let queries = config.queries(false, &Selection::default());
+
+        assert!(!queries.iter().any(|q| q.id == "FlagsNotList"));
+        assert!(!queries.iter().any(|q| q.id == "RefsTagsWithRev"));
+        assert!(queries
+            .iter()
+            .any(|q| q.id == "SRIHash" && q.severity == Severity::Error));
+        assert!(queries
+            .iter()
+            .any(|q| q.id == "ArgsToOptionalIsList" && q.severity == Severity::Hint));
+    }
+
+    #[test]
+    fn config_unknown_rule() {
+        assert!(Config::parse("[rules.Typo]\nenabled = false", Path::new("/")).is_err());
+    }
+
+    #[test]
+    fn config_exclude() {
+        let config = config(r#"exclude = ["pkgs/test/**"]"#);
+
+        assert!(config.is_excluded(Path::new("/nixpkgs/pkgs/test/foo/default.nix")));
+        assert!(!config.is_excluded(Path::new("/nixpkgs/pkgs/tools/foo/default.nix")));
+    }
+}
@@ -1,31 +1,250 @@
-use std::fs::read_to_string;
+use std::{
+    collections::HashMap,
+    env::current_dir,
+    fs::{read_to_string, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 
-use crate::query::{AMatch, QueryType};
-use ariadne::{Color, Label, Report as CliReport, ReportKind as CliReportKind, Source};
+use crate::find_lints::get_tree;
+use crate::fix::{match_edits, Edit, FileFix};
+use crate::query::{AMatch, AQuery, QueryType, Severity};
+use ariadne::{
+    Color, Config as CliConfig, Label, Report as CliReport, ReportKind as CliReportKind, Source,
+};
+use similar::TextDiff;
+use tree_sitter::Tree;
 
-#[derive(Clone, Debug, clap::ValueEnum)]
+mod github;
+mod gitlab;
+mod html;
+mod json;
+mod markdown;
+mod rdjson;
+mod sarif;
+mod xml;
+
+#[derive(Clone, Debug, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DisplayFormats {
     Ariadne,
+    /// a versioned report object, see `nixpkgs-lint schema`
     Json,
+    /// one compact json object per line, printed as soon as a file is linted
+    Ndjson,
+    /// SARIF 2.1.0 for code scanning dashboards
+    Sarif,
+    /// GitHub Actions workflow commands, shown as annotations of pull requests
+    Github,
+    /// reviewdog's rdjson, with the automatic fixes as suggestions
+    Rdjson,
+    Checkstyle,
+    /// JUnit XML, every file is a test case
+    Junit,
+    /// GitLab Code Quality
+    Gitlab,
+    /// `path:line:col: severity [RuleID] message (fix)` like gcc, for editors
+    Compact,
+    /// a summary for pull request comments
+    Markdown,
+    /// a self-contained page, filterable by rule and file
+    Html,
+}
+
+impl DisplayFormats {
+    /// Documents are written even without matches, an empty report tells the tools
+    /// reading it that the issues are gone.
+    pub fn is_document(&self) -> bool {
+        matches!(
+            self,
+            DisplayFormats::Json
+                | DisplayFormats::Sarif
+                | DisplayFormats::Rdjson
+                | DisplayFormats::Checkstyle
+                | DisplayFormats::Junit
+                | DisplayFormats::Gitlab
+                | DisplayFormats::Markdown
+                | DisplayFormats::Html
+        )
+    }
+}
+
+/// What the formats know about the run besides the matches
+#[derive(Debug, Default)]
+pub struct RunInfo {
+    /// all linted files, including those without matches
+    pub files: Vec<String>,
+    /// links to the files start with this, e.g. `https://github.com/NixOS/nixpkgs/blob/master`
+    pub base_url: Option<String>,
+    /// the lints of the run, with the severities of their config
+    pub rules: Vec<AQuery>,
+    pub duration: Duration,
+}
+
+/// The contents of the matched files, trimmed like they were for find_lints
+#[derive(Default)]
+struct Sources {
+    files: HashMap<String, (String, Tree)>,
+}
+
+impl Sources {
+    fn get(&mut self, file: &str) -> &(String, Tree) {
+        self.files.entry(file.to_owned()).or_insert_with(|| {
+            let text = read_to_string(file).unwrap().trim().to_owned();
+            let tree = get_tree(&text);
+            (text, tree)
+        })
+    }
+
+    fn text(&mut self, file: &str) -> &str {
+        &self.get(file).0
+    }
+
+    /// The edits of the automatic fix of `m`, if it has one.
+    fn edits(&mut self, m: &AMatch) -> Option<Vec<Edit>> {
+        let (text, tree) = self.get(&m.file);
+        match_edits(m, text, tree).ok()
+    }
+}
+
+/// Where the tool is documented, for reports which link to it
+const HOMEPAGE: &str = env!("CARGO_PKG_REPOSITORY");
+
+/// The 1-based line and column of the byte offset `byte` of `text`
+fn position(text: &str, byte: usize) -> (usize, usize) {
+    let line_start = text[..byte].rfind('\n').map_or(0, |i| i + 1);
+    (
+        text[..byte].matches('\n').count() + 1,
+        byte - line_start + 1,
+    )
+}
+
+/// `file` relative to the current directory, as code review tools expect.
+fn relative_path(file: &str) -> String {
+    let cwd = current_dir().unwrap();
+    let path = Path::new(file);
+    path.strip_prefix(&cwd)
+        .or_else(|_| path.strip_prefix("."))
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The severity in the words of gcc, which editors know
+fn compact_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+        Severity::Hint => "note",
+    }
+}
+
+/// The ariadne report kind and label color of a severity
+fn report_kind(severity: Severity) -> (CliReportKind<'static>, Color) {
+    match severity {
+        Severity::Error => (CliReportKind::Error, Color::Red),
+        Severity::Warning => (CliReportKind::Warning, Color::Yellow),
+        Severity::Info => (CliReportKind::Advice, Color::Magenta),
+        Severity::Hint => (CliReportKind::Custom("Hint", Color::Cyan), Color::Cyan),
+    }
+}
+
+/// A report written to a file next to the one on the terminal, `--output FORMAT=PATH`
+#[derive(Clone, Debug)]
+pub struct Output {
+    pub format: DisplayFormats,
+    pub path: PathBuf,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((format, path)) = s.split_once('=') else {
+            return Err(format!("'{s}' isn't FORMAT=PATH"));
+        };
+        let format = <DisplayFormats as clap::ValueEnum>::from_str(format, true)
+            .map_err(|_| format!("unknown format '{format}'"))?;
+        if path.is_empty() {
+            return Err(format!("'{s}' has no path"));
+        }
+        Ok(Output {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+pub fn write_matches(
+    out: &mut impl Write,
+    display_format: &DisplayFormats,
+    run: &RunInfo,
+    match_vec: &[AMatch],
+) -> io::Result<()> {
+    write_report(out, display_format, run, match_vec, true)
+}
+
+/// Writes the report even without matches, so a report of an earlier run doesn't stay around.
+pub fn write_output(output: &Output, run: &RunInfo, match_vec: &[AMatch]) -> Result<(), String> {
+    let error = |e: io::Error| format!("couldn't write {}: {e}", output.path.display());
+    let mut out = BufWriter::new(File::create(&output.path).map_err(error)?);
+    write_report(&mut out, &output.format, run, match_vec, false).map_err(error)?;
+    out.flush().map_err(error)
 }
 
-pub fn print_matches(display_format: &DisplayFormats, match_vec: &[AMatch]) {
+fn write_report(
+    out: &mut impl Write,
+    display_format: &DisplayFormats,
+    run: &RunInfo,
+    match_vec: &[AMatch],
+    colored: bool,
+) -> io::Result<()> {
     match display_format {
-        DisplayFormats::Json => {
-            let serialized_match = serde_json::to_string_pretty(match_vec).unwrap();
-            println!("{serialized_match}");
+        DisplayFormats::Json => json::write(out, run, match_vec)?,
+        DisplayFormats::Ndjson => {
+            for m in match_vec {
+                writeln!(out, "{}", serde_json::to_string(m).unwrap())?;
+            }
+        }
+        DisplayFormats::Sarif => sarif::write(out, match_vec)?,
+        DisplayFormats::Github => github::write(out, match_vec)?,
+        DisplayFormats::Rdjson => rdjson::write(out, match_vec)?,
+        DisplayFormats::Checkstyle => xml::write_checkstyle(out, &run.files, match_vec)?,
+        DisplayFormats::Junit => xml::write_junit(out, &run.files, match_vec)?,
+        DisplayFormats::Markdown => markdown::write(out, run, match_vec)?,
+        DisplayFormats::Html => html::write(out, run, match_vec)?,
+        DisplayFormats::Gitlab => gitlab::write(out, match_vec)?,
+        DisplayFormats::Compact => {
+            for m in match_vec {
+                writeln!(
+                    out,
+                    "{}:{}:{}: {} [{}] {} ({})",
+                    relative_path(&m.file),
+                    m.line,
+                    m.column,
+                    compact_severity(m.severity),
+                    m.rule,
+                    m.message,
+                    m.fix
+                )?;
+            }
         }
         DisplayFormats::Ariadne => {
             for m in match_vec {
                 let src_id = m.file.as_str();
-                let mut report =
-                    CliReport::build(CliReportKind::Advice, src_id, m.byte_range.start)
-                        .with_message(&m.message)
-                        .with_label(
-                            Label::new((src_id, m.byte_range.start..m.byte_range.end))
-                                .with_message(&m.fix)
-                                .with_color(Color::Magenta),
-                        );
+                let (kind, color) = report_kind(m.severity);
+                let mut report = CliReport::build(kind, src_id, m.byte_range.start)
+                    .with_config(CliConfig::default().with_color(colored))
+                    .with_code(&m.rule)
+                    .with_message(&m.message)
+                    .with_label(
+                        Label::new((src_id, m.byte_range.start..m.byte_range.end))
+                            .with_message(&m.fix)
+                            .with_color(color),
+                    );
 
                 if let QueryType::List = m.query.type_of_query {
                     report = report.with_label(
@@ -35,14 +254,155 @@ pub fn print_matches(display_format: &DisplayFormats, match_vec: &[AMatch]) {
                     );
                 };
 
-                report
-                    .finish()
-                    .print((
+                report.finish().write(
+                    (
                         src_id,
                         Source::from(read_to_string(&m.file).unwrap().trim().to_owned()),
-                    ))
-                    .unwrap();
+                    ),
+                    &mut *out,
+                )?;
             }
         }
     }
+    Ok(())
+}
+
+/// The JSON Schema of `--format json`
+pub fn json_schema() -> String {
+    json::schema()
+}
+
+/// Writes the fixes as a unified diff which `git apply` accepts when run from the current directory.
+pub fn write_diff(out: &mut impl Write, fixes: &[FileFix]) -> io::Result<()> {
+    for f in fixes.iter().filter(|f| f.is_changed()) {
+        let path = relative_path(&f.file);
+
+        write!(
+            out,
+            "{}",
+            TextDiff::from_lines(&f.original, &f.fixed)
+                .unified_diff()
+                .header(&format!("a/{path}"), &format!("b/{path}"))
+        )?;
+    }
+    Ok(())
+}
+
+/// The matches of the default lints in `file`, for the reporters' tests
+#[cfg(test)]
+fn lint_file(file: &str) -> Vec<AMatch> {
+    let mut queries: Vec<AQuery> = Vec::new();
+    crate::queries::add_default_queries(&mut queries);
+    crate::find_lints::find_lints(file, read_to_string(file).unwrap().trim(), &queries, &false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        lint_file, write_diff, write_matches, write_report, DisplayFormats, Output, RunInfo,
+    };
+    use crate::fix::fix_matches;
+
+    #[test]
+    fn compact() {
+        let file = "assets/simple.nix";
+        let matches = lint_file(file);
+
+        let mut out = Vec::new();
+        write_matches(
+            &mut out,
+            &DisplayFormats::Compact,
+            &RunInfo::default(),
+            &matches,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "assets/simple.nix:5:5: warning [BuildTimeToolInBuildInputs] build time tool in buildInputs (move this from buildInputs to nativeBuildInputs)
+assets/simple.nix:6:5: warning [BuildTimeToolInBuildInputs] build time tool in buildInputs (move this from buildInputs to nativeBuildInputs)
+"
+        );
+    }
+
+    #[test]
+    fn ndjson() {
+        let file = "assets/simple.nix";
+        let matches = lint_file(file);
+
+        let mut out = Vec::new();
+        write_matches(
+            &mut out,
+            &DisplayFormats::Ndjson,
+            &RunInfo::default(),
+            &matches,
+        )
+        .unwrap();
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            serde_json::to_value(&matches).unwrap().as_array().unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn output() {
+        let output: Output = "SARIF=reports/out.sarif".parse().unwrap();
+        assert!(matches!(output.format, DisplayFormats::Sarif));
+        assert_eq!(output.path.to_str(), Some("reports/out.sarif"));
+
+        assert!("out.sarif".parse::<Output>().is_err());
+        assert!("yaml=out.yaml".parse::<Output>().is_err());
+        assert!("json=".parse::<Output>().is_err());
+    }
+
+    #[test]
+    fn ariadne_without_color() {
+        let file = "assets/simple.nix";
+        let matches = lint_file(file);
+
+        let mut out = Vec::new();
+        write_report(
+            &mut out,
+            &DisplayFormats::Ariadne,
+            &RunInfo::default(),
+            &matches,
+            false,
+        )
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("[BuildTimeToolInBuildInputs] Warning:"));
+        assert!(!out.contains('\x1b'));
+    }
+
+    #[test]
+    fn diff() {
+        let file = "assets/simple.nix";
+        let matches = lint_file(file);
+
+        let mut out = Vec::new();
+        write_diff(&mut out, &fix_matches(matches)).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "--- a/assets/simple.nix
++++ b/assets/simple.nix
+@@ -2,6 +2,8 @@
+ 
+ stdenv.mkDerivation {
+   buildInputs = [
++  ];
++  nativeBuildInputs = [
+     pkg-config
+     cmake
+   ];
+"
+        );
+    }
 }
@@ -0,0 +1,73 @@
+//! [workflow commands](https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions)
+//! which GitHub Actions shows as annotations of the pull request
+
+use std::io::{self, Write};
+
+use super::relative_path;
+use crate::query::{AMatch, Severity};
+
+fn command(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Hint => "notice",
+    }
+}
+
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+pub fn write(out: &mut impl Write, match_vec: &[AMatch]) -> io::Result<()> {
+    for m in match_vec {
+        writeln!(
+            out,
+            "::{} file={},line={},col={},endLine={},endColumn={},title={}::{}",
+            command(m.severity),
+            escape_property(&relative_path(&m.file)),
+            m.line,
+            m.column,
+            m.end_line,
+            m.end_column,
+            escape_property(&m.rule),
+            escape_data(&format!("{}: {}", m.message, m.fix)),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_data, escape_property, write};
+    use crate::display::lint_file;
+
+    #[test]
+    fn github_escape() {
+        assert_eq!(escape_data("50%\na: b, c"), "50%25%0Aa: b, c");
+        assert_eq!(escape_property("a: b, c"), "a%3A b%2C c");
+    }
+
+    #[test]
+    fn github_annotations() {
+        let file = "assets/simple.nix";
+        let matches = lint_file(file);
+
+        let mut out = Vec::new();
+        write(&mut out, &matches).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap().lines().next(),
+            Some(
+                "::warning file=assets/simple.nix,line=5,col=5,endLine=5,endColumn=15,\
+                 title=BuildTimeToolInBuildInputs::build time tool in buildInputs: \
+                 move this from buildInputs to nativeBuildInputs"
+            )
+        );
+    }
+}
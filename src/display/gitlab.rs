@@ -0,0 +1,123 @@
+//! [GitLab Code Quality](https://docs.gitlab.com/ee/ci/testing/code_quality.html#code-quality-report-format) reports
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::relative_path;
+use crate::query::{AMatch, Severity};
+
+#[derive(Serialize)]
+struct Issue {
+    description: String,
+    check_name: String,
+    fingerprint: String,
+    severity: &'static str,
+    location: Location,
+}
+
+#[derive(Serialize)]
+struct Location {
+    path: String,
+    lines: Lines,
+}
+
+#[derive(Serialize)]
+struct Lines {
+    begin: usize,
+}
+
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "critical",
+        Severity::Warning => "major",
+        Severity::Info => "minor",
+        Severity::Hint => "info",
+    }
+}
+
+/// GitLab compares reports of branches by fingerprint, so it doesn't depend on the position of
+/// the match. The same text matched again in a file is told apart by `occurrence`.
+fn fingerprint(rule: &str, path: &str, matched: &str, occurrence: usize) -> String {
+    let mut hasher = Sha256::new();
+    for part in [rule, path, matched, &occurrence.to_string()] {
+        hasher.update(part);
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+pub fn write(out: &mut impl Write, match_vec: &[AMatch]) -> io::Result<()> {
+    let mut occurrences: HashMap<(&str, &str, &str), usize> = HashMap::new();
+
+    let issues: Vec<Issue> = match_vec
+        .iter()
+        .map(|m| {
+            let path = relative_path(&m.file);
+            let occurrence = occurrences
+                .entry((&m.rule, &m.file, &m.matched))
+                .or_default();
+            *occurrence += 1;
+
+            Issue {
+                description: format!("{}: {}", m.message, m.fix),
+                check_name: m.rule.to_owned(),
+                fingerprint: fingerprint(&m.rule, &path, &m.matched, *occurrence),
+                severity: severity(m.severity),
+                location: Location {
+                    path,
+                    lines: Lines { begin: m.line },
+                },
+            }
+        })
+        .collect();
+
+    writeln!(out, "{}", serde_json::to_string_pretty(&issues).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::write;
+    use crate::{find_lints::find_lints, queries::add_default_queries, query::AQuery};
+
+    fn fingerprints(expr: &str) -> Vec<String> {
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_default_queries(&mut queries);
+        let matches = find_lints("default.nix", expr, &queries, &false);
+
+        let mut out = Vec::new();
+        write(&mut out, &matches).unwrap();
+        let issues: Value = serde_json::from_slice(&out).unwrap();
+        issues
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["fingerprint"].as_str().unwrap().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn gitlab_fingerprints() {
+        let before = fingerprints("{ buildInputs = [ cmake ]; }");
+        let moved = fingerprints(
+            "{\n  pname = \"foo\";\n  buildInputs = [ zlib cmake ];\n  nativeBuildInputs = [ cmake ];\n}",
+        );
+        let twice =
+            fingerprints("{ buildInputs = [ cmake ]; passthru = { buildInputs = [ cmake ]; }; }");
+
+        assert_eq!(before, moved);
+        assert_eq!(twice.len(), 2);
+        assert_eq!(twice[0], before[0]);
+        assert_ne!(twice[0], twice[1]);
+    }
+}
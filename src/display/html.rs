@@ -0,0 +1,250 @@
+//! A self-contained page to browse big runs, filterable by rule and file
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::{self, Write},
+    ops::Range,
+};
+
+use tree_sitter::{Node, Tree};
+
+use super::{position, relative_path, xml::escape, RunInfo, Sources};
+use crate::query::AMatch;
+use crate::rules::value_name;
+
+/// lines shown around a match
+const CONTEXT_LINES: usize = 2;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { text-align: left; vertical-align: top; padding: 0.3em 0.6em; border-bottom: 1px solid #ddd; }
+pre { margin: 0; background: #f6f8fa; padding: 0.4em; }
+mark { background: #fff3b0; }
+.ln { color: #999; user-select: none; }
+.comment { color: #6a737d; }
+.string { color: #032f62; }
+.keyword { color: #d73a49; }
+.number { color: #005cc5; }
+.attr { color: #6f42c1; }
+.error { color: #cb2431; } .warning { color: #b08800; } .info, .hint { color: #005cc5; }
+#filters { margin: 1em 0; }
+";
+
+const SCRIPT: &str = "
+const rule = document.getElementById('rule');
+const file = document.getElementById('file');
+function filter() {
+  for (const row of document.querySelectorAll('#matches tbody tr')) {
+    row.hidden = (rule.value && row.dataset.rule !== rule.value)
+      || !row.dataset.file.includes(file.value);
+  }
+}
+rule.addEventListener('change', filter);
+file.addEventListener('input', filter);
+";
+
+/// The highlighting class of a leaf node
+fn class(node: &Node) -> Option<&'static str> {
+    let parent = node.parent().map(|p| p.kind()).unwrap_or_default();
+    match node.kind() {
+        "comment" => Some("comment"),
+        "let" | "in" | "with" | "rec" | "inherit" | "if" | "then" | "else" | "assert" | "or" => {
+            Some("keyword")
+        }
+        "integer_expression" | "float_expression" | "integer" | "float" => Some("number"),
+        "identifier" if parent == "attrpath" => Some("attr"),
+        _ if matches!(parent, "string_expression" | "indented_string_expression") => Some("string"),
+        _ => None,
+    }
+}
+
+fn leaves<'t>(node: Node<'t>, range: &Range<usize>, out: &mut Vec<Node<'t>>) {
+    if node.end_byte() <= range.start || node.start_byte() >= range.end {
+        return;
+    }
+    if node.child_count() == 0 {
+        out.push(node);
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        leaves(child, range, out);
+    }
+}
+
+/// The lines of `range` as html with line numbers, syntax highlighting and `mark` in `<mark>`.
+fn highlight(text: &str, tree: &Tree, range: Range<usize>, mark: &Range<usize>) -> String {
+    let mut nodes = Vec::new();
+    leaves(tree.root_node(), &range, &mut nodes);
+    let classes: Vec<(Range<usize>, &str)> = nodes
+        .iter()
+        .filter_map(|n| class(n).map(|c| (n.byte_range(), c)))
+        .collect();
+
+    let mut bounds: Vec<usize> = vec![range.start, range.end, mark.start, mark.end];
+    bounds.extend(classes.iter().flat_map(|(r, _)| [r.start, r.end]));
+    bounds.extend(
+        text[range.clone()]
+            .match_indices('\n')
+            .map(|(i, _)| range.start + i + 1),
+    );
+    bounds.retain(|b| range.contains(b) || *b == range.end);
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let mut line = position(text, range.start).0;
+    let mut html = format!("<span class=\"ln\">{line:>4} </span>");
+    let mut marked = false;
+    for segment in bounds.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        if start > range.start && text.as_bytes()[start - 1] == b'\n' {
+            line += 1;
+            html.push_str(&format!("<span class=\"ln\">{line:>4} </span>"));
+        }
+        if mark.contains(&start) != marked {
+            marked = !marked;
+            html.push_str(if marked { "<mark>" } else { "</mark>" });
+        }
+        let code = escape(&text[start..end]);
+        match classes.iter().find(|(r, _)| r.contains(&start)) {
+            Some((_, class)) => html.push_str(&format!("<span class=\"{class}\">{code}</span>")),
+            None => html.push_str(&code),
+        }
+    }
+    if marked {
+        html.push_str("</mark>");
+    }
+    html
+}
+
+/// The byte range of the lines around `range`
+fn context(text: &str, range: &Range<usize>) -> Range<usize> {
+    let mut start = range.start;
+    for _ in 0..=CONTEXT_LINES {
+        start = text[..start].rfind('\n').unwrap_or(0);
+    }
+    if start > 0 {
+        start += 1;
+    }
+    let mut end = range.end;
+    for _ in 0..=CONTEXT_LINES {
+        end = text[end..].find('\n').map_or(text.len(), |i| end + i + 1);
+    }
+    start..text[..end].trim_end_matches('\n').len()
+}
+
+pub fn write(out: &mut impl Write, run: &RunInfo, match_vec: &[AMatch]) -> io::Result<()> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for m in match_vec {
+        *counts.entry(&m.rule).or_default() += 1;
+    }
+    let files = match_vec
+        .iter()
+        .map(|m| &m.file)
+        .collect::<HashSet<_>>()
+        .len();
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html lang=\"en\">")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>nixpkgs-lint report</title>")?;
+    writeln!(out, "<style>{STYLE}</style>")?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    writeln!(out, "<h1>nixpkgs-lint report</h1>")?;
+    writeln!(
+        out,
+        "<p>{} issues in {files} of {} files.</p>",
+        match_vec.len(),
+        run.files.len()
+    )?;
+
+    writeln!(out, "<table id=\"rules\">")?;
+    writeln!(out, "<thead><tr><th>Rule</th><th>Count</th></tr></thead>")?;
+    writeln!(out, "<tbody>")?;
+    for (rule, count) in &counts {
+        writeln!(out, "<tr><td>{}</td><td>{count}</td></tr>", escape(rule))?;
+    }
+    writeln!(out, "</tbody>")?;
+    writeln!(out, "</table>")?;
+
+    writeln!(out, "<div id=\"filters\">")?;
+    writeln!(
+        out,
+        "<select id=\"rule\"><option value=\"\">all rules</option>"
+    )?;
+    for (rule, count) in &counts {
+        let rule = escape(rule);
+        writeln!(out, "<option value=\"{rule}\">{rule} ({count})</option>")?;
+    }
+    writeln!(out, "</select>")?;
+    writeln!(out, "<input id=\"file\" placeholder=\"file\">")?;
+    writeln!(out, "</div>")?;
+
+    writeln!(out, "<table id=\"matches\">")?;
+    writeln!(
+        out,
+        "<thead><tr><th>File</th><th>Severity</th><th>Rule</th><th>Message</th><th>Code</th></tr></thead>"
+    )?;
+    writeln!(out, "<tbody>")?;
+    let mut sources = Sources::default();
+    for m in match_vec {
+        let path = escape(&relative_path(&m.file));
+        let location = format!("{path}:{}:{}", m.line, m.column);
+        let location = match &run.base_url {
+            Some(base_url) => format!(
+                "<a href=\"{}/{path}#L{}\">{location}</a>",
+                escape(base_url.trim_end_matches('/')),
+                m.line
+            ),
+            None => location,
+        };
+        let severity = value_name(m.severity);
+        let (text, tree) = sources.get(&m.file);
+        let code = highlight(text, tree, context(text, &m.byte_range), &m.byte_range);
+
+        writeln!(
+            out,
+            "<tr data-rule=\"{rule}\" data-file=\"{path}\"><td>{location}</td><td class=\"{severity}\">{severity}</td><td>{rule}</td><td>{}<br>{}</td><td><pre><code>{code}</code></pre></td></tr>",
+            escape(&m.message),
+            escape(&m.fix),
+            rule = escape(&m.rule),
+        )?;
+    }
+    writeln!(out, "</tbody>")?;
+    writeln!(out, "</table>")?;
+    writeln!(out, "<script>{SCRIPT}</script>")?;
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{context, highlight};
+    use crate::find_lints::get_tree;
+
+    #[test]
+    fn html_highlight() {
+        let text = "{\n  # tools\n  buildInputs = [ cmake ];\n}";
+        let tree = get_tree(text);
+        let cmake = text.find("cmake").unwrap();
+
+        assert_eq!(
+            highlight(text, &tree, 2..text.len() - 2, &(cmake..cmake + 5)),
+            "<span class=\"ln\">   2 </span>  <span class=\"comment\"># tools</span>\n\
+             <span class=\"ln\">   3 </span>  <span class=\"attr\">buildInputs</span> = [ \
+             <mark>cmake</mark> ];"
+        );
+    }
+
+    #[test]
+    fn html_context() {
+        let text = "1\n2\n3\n4\n5\n6\n7";
+        let four = text.find('4').unwrap();
+
+        assert_eq!(&text[context(text, &(four..four + 1))], "2\n3\n4\n5\n6");
+        assert_eq!(&text[context(text, &(0..1))], "1\n2\n3");
+    }
+}
@@ -0,0 +1,146 @@
+//! The report of `--format json`, its schema is committed to `schema/report.json`
+
+use std::io::{self, Write};
+
+use schemars::{schema_for, JsonSchema};
+use serde::Serialize;
+
+use super::RunInfo;
+use crate::query::{AMatch, Category, Maturity, Severity};
+
+/// Bumped when a field is removed or changes its meaning, new fields don't break consumers.
+const SCHEMA_VERSION: u32 = 1;
+
+/// The report of a nixpkgs-lint run
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct Report<'a> {
+    /// the version of this schema
+    schema_version: u32,
+    /// the version of nixpkgs-lint
+    tool_version: &'static str,
+    /// the lints which were run, sorted by id
+    rules: Vec<Rule<'a>>,
+    /// how many files were linted
+    file_count: usize,
+    timing: Timing,
+    matches: &'a [AMatch],
+}
+
+/// A lint with the severity it had in this run
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct Rule<'a> {
+    id: &'a str,
+    category: Category,
+    maturity: Maturity,
+    severity: Severity,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct Timing {
+    /// the time it took to find the files and lint them
+    duration_ms: u64,
+}
+
+pub fn write(out: &mut impl Write, run: &RunInfo, match_vec: &[AMatch]) -> io::Result<()> {
+    let mut rules: Vec<Rule> = run
+        .rules
+        .iter()
+        .map(|q| Rule {
+            id: &q.id,
+            category: q.category,
+            maturity: q.maturity,
+            severity: q.severity,
+        })
+        .collect();
+    rules.sort_by_key(|r| r.id);
+
+    let report = Report {
+        schema_version: SCHEMA_VERSION,
+        tool_version: env!("CARGO_PKG_VERSION"),
+        rules,
+        file_count: run.files.len(),
+        timing: Timing {
+            duration_ms: run.duration.as_millis().try_into().unwrap_or(u64::MAX),
+        },
+        matches: match_vec,
+    };
+    writeln!(out, "{}", serde_json::to_string_pretty(&report).unwrap())
+}
+
+/// The JSON Schema of the report, generated from its types
+pub fn schema() -> String {
+    serde_json::to_string_pretty(&schema_for!(Report)).unwrap() + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use serde_json::Value;
+
+    use super::{schema, write, SCHEMA_VERSION};
+    use crate::display::{lint_file, RunInfo};
+    use crate::{queries::add_default_queries, query::AQuery};
+
+    #[test]
+    fn json_schema_is_committed() {
+        assert_eq!(
+            read_to_string("schema/report.json").unwrap(),
+            schema(),
+            "the report's types changed, run `nixpkgs-lint schema > schema/report.json` \
+             and bump SCHEMA_VERSION if the change breaks consumers"
+        );
+    }
+
+    #[test]
+    fn json_report() {
+        let file = "assets/simple.nix";
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_default_queries(&mut queries);
+        let matches = lint_file(file);
+        let run = RunInfo {
+            files: vec![file.to_string()],
+            rules: queries,
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        write(&mut out, &run, &matches).unwrap();
+        let report: Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(report["schemaVersion"], SCHEMA_VERSION);
+        assert_eq!(report["toolVersion"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(report["fileCount"], 1);
+        assert_eq!(report["rules"].as_array().unwrap().len(), run.rules.len());
+        assert_eq!(
+            report["rules"][0],
+            serde_json::json!({
+                "id": "ArgsToOptionalIsList",
+                "category": "general",
+                "maturity": "stable",
+                "severity": "warning"
+            })
+        );
+        assert_eq!(report["matches"], serde_json::to_value(&matches).unwrap());
+        assert_eq!(report["matches"][0]["endLine"], 5);
+    }
+
+    #[test]
+    fn json_report_without_matches() {
+        let run = RunInfo {
+            files: vec!["assets/clean.nix".to_string()],
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        write(&mut out, &run, &[]).unwrap();
+        let report: Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(report["schemaVersion"], SCHEMA_VERSION);
+        assert_eq!(report["fileCount"], 1);
+        assert_eq!(report["matches"], serde_json::json!([]));
+    }
+}
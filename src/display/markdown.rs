@@ -0,0 +1,153 @@
+//! A summary for pull request comments
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use super::{relative_path, xml::escape, RunInfo, Sources};
+use crate::query::AMatch;
+use crate::rules::value_name;
+
+fn escape_markdown(s: &str) -> String {
+    s.chars()
+        .flat_map(|c| match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+pub fn write(out: &mut impl Write, run: &RunInfo, match_vec: &[AMatch]) -> io::Result<()> {
+    writeln!(out, "## nixpkgs-lint")?;
+    writeln!(out)?;
+
+    let mut by_file: BTreeMap<String, Vec<&AMatch>> = BTreeMap::new();
+    for m in match_vec {
+        by_file.entry(relative_path(&m.file)).or_default().push(m);
+    }
+
+    if match_vec.is_empty() {
+        writeln!(out, "No issues in {} files.", run.files.len())?;
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "Found **{}** issues in **{}** of {} files.",
+        match_vec.len(),
+        by_file.len(),
+        run.files.len()
+    )?;
+    writeln!(out)?;
+
+    let mut counts: BTreeMap<(&str, String), usize> = BTreeMap::new();
+    for m in match_vec {
+        *counts.entry((&m.rule, value_name(m.severity))).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    writeln!(out, "| Rule | Severity | Count |")?;
+    writeln!(out, "| --- | --- | ---: |")?;
+    for ((rule, severity), count) in counts {
+        writeln!(out, "| `{rule}` | {severity} | {count} |")?;
+    }
+
+    let mut sources = Sources::default();
+    for (path, matches) in by_file {
+        writeln!(out)?;
+        writeln!(out, "<details>")?;
+        writeln!(
+            out,
+            "<summary><code>{}</code> ({})</summary>",
+            escape(&path),
+            matches.len()
+        )?;
+        writeln!(out)?;
+
+        for m in matches {
+            let line = match &run.base_url {
+                Some(base_url) => format!(
+                    "[line {}]({}/{path}#L{})",
+                    m.line,
+                    base_url.trim_end_matches('/'),
+                    m.line
+                ),
+                None => format!("line {}", m.line),
+            };
+            writeln!(
+                out,
+                "- {line}: {} `{}` {}, {}",
+                value_name(m.severity),
+                m.rule,
+                escape_markdown(&m.message),
+                escape_markdown(&m.fix)
+            )?;
+
+            let text = sources.text(&m.file);
+            let start = text[..m.byte_range.start].rfind('\n').map_or(0, |i| i + 1);
+            let end = text[m.byte_range.end..]
+                .find('\n')
+                .map_or(text.len(), |i| m.byte_range.end + i);
+            writeln!(out)?;
+            writeln!(out, "  ```nix")?;
+            for line in text[start..end].lines() {
+                writeln!(out, "  {line}")?;
+            }
+            writeln!(out, "  ```")?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "</details>")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_markdown, write};
+    use crate::display::lint_file;
+    use crate::display::RunInfo;
+
+    #[test]
+    fn markdown_escape() {
+        assert_eq!(escape_markdown("*Flags_ `x`"), "\\*Flags\\_ \\`x\\`");
+    }
+
+    #[test]
+    fn markdown_summary() {
+        let file = "assets/var.nix";
+        let matches = lint_file(file);
+        let run = RunInfo {
+            files: vec![file.to_string(), "assets/simple.nix".to_string()],
+            base_url: Some("https://github.com/NixOS/nixpkgs/blob/master/".to_string()),
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        write(&mut out, &run, &matches).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"## nixpkgs-lint
+
+Found **1** issues in **1** of 2 files.
+
+| Rule | Severity | Count |
+| --- | --- | ---: |
+| `FlagsNotList` | warning | 1 |
+
+<details>
+<summary><code>assets/var.nix</code> (1)</summary>
+
+- [line 4](https://github.com/NixOS/nixpkgs/blob/master/assets/var.nix#L4): warning `FlagsNotList` \*Flags not a list, convert to a list
+
+  ```nix
+    someFlags = "--flags-should-be-lists-of-strings";
+  ```
+
+</details>
+"#
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! [reviewdog diagnostic format](https://github.com/reviewdog/reviewdog/tree/master/proto/rdf)
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use super::{position, relative_path, Sources, HOMEPAGE};
+use crate::query::{AMatch, Severity};
+
+#[derive(Serialize)]
+struct DiagnosticResult {
+    source: Source,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize)]
+struct Source {
+    name: &'static str,
+    url: &'static str,
+}
+
+#[derive(Serialize)]
+struct Diagnostic {
+    message: String,
+    location: Location,
+    severity: &'static str,
+    code: Code,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<Suggestion>,
+}
+
+#[derive(Serialize)]
+struct Location {
+    path: String,
+    range: Range,
+}
+
+#[derive(Serialize)]
+struct Range {
+    start: Position,
+    end: Position,
+}
+
+impl Range {
+    fn of_match(m: &AMatch) -> Self {
+        Range {
+            start: Position {
+                line: m.line,
+                column: m.column,
+            },
+            end: Position {
+                line: m.end_line,
+                column: m.end_column,
+            },
+        }
+    }
+
+    fn new(text: &str, range: &std::ops::Range<usize>) -> Self {
+        let position = |byte| {
+            let (line, column) = position(text, byte);
+            Position { line, column }
+        };
+        Range {
+            start: position(range.start),
+            end: position(range.end),
+        }
+    }
+}
+
+/// 1-based, columns count bytes
+#[derive(Serialize)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Serialize)]
+struct Code {
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Suggestion {
+    range: Range,
+    text: String,
+}
+
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "ERROR",
+        Severity::Warning => "WARNING",
+        Severity::Info | Severity::Hint => "INFO",
+    }
+}
+
+pub fn write(out: &mut impl Write, match_vec: &[AMatch]) -> io::Result<()> {
+    let mut sources = Sources::default();
+
+    let diagnostics = match_vec
+        .iter()
+        .map(|m| {
+            let edits = sources.edits(m).unwrap_or_default();
+            let text = sources.text(&m.file);
+
+            Diagnostic {
+                message: format!("{}: {}", m.message, m.fix),
+                location: Location {
+                    path: relative_path(&m.file),
+                    range: Range::of_match(m),
+                },
+                severity: severity(m.severity),
+                code: Code {
+                    value: m.rule.to_owned(),
+                },
+                suggestions: edits
+                    .into_iter()
+                    .map(|e| Suggestion {
+                        range: Range::new(text, &e.range),
+                        text: e.replacement,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let result = DiagnosticResult {
+        source: Source {
+            name: "nixpkgs-lint",
+            url: HOMEPAGE,
+        },
+        diagnostics,
+    };
+
+    writeln!(out, "{}", serde_json::to_string_pretty(&result).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::write;
+    use crate::display::lint_file;
+
+    #[test]
+    fn rdjson_suggestions() {
+        let file = "assets/optional_a_list.nix";
+        let matches = lint_file(file);
+
+        let mut out = Vec::new();
+        write(&mut out, &matches).unwrap();
+        let result: Value = serde_json::from_slice(&out).unwrap();
+        let diagnostic = &result["diagnostics"][0];
+
+        assert_eq!(
+            result["source"]["url"],
+            "https://github.com/natsukium/nixpkgs-lint"
+        );
+        assert_eq!(diagnostic["code"]["value"], "ArgsToOptionalIsList");
+        assert_eq!(diagnostic["severity"], "WARNING");
+        assert_eq!(
+            diagnostic["suggestions"],
+            json!([{
+                "range": {
+                    "start": { "line": 5, "column": 11 },
+                    "end": { "line": 5, "column": 19 }
+                },
+                "text": "optionals"
+            }])
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) logs
+
+use std::io::{self, Write};
+
+use clap::crate_version;
+use serde::Serialize;
+
+use super::{relative_path, Sources, HOMEPAGE};
+use crate::queries::QUERIES;
+use crate::query::{AMatch, AQuery, Category, Maturity, Severity};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    /// the SARIF default, explicit for viewers which assume bytes
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+impl Message {
+    fn new(text: impl Into<String>) -> Self {
+        Message { text: text.into() }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    short_description: Message,
+    full_description: Message,
+    help: Message,
+    default_configuration: Configuration,
+    properties: RuleProperties,
+}
+
+#[derive(Serialize)]
+struct Configuration {
+    level: &'static str,
+}
+
+#[derive(Serialize)]
+struct RuleProperties {
+    category: Category,
+    maturity: Maturity,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    rule_index: usize,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+/// The 1-based line and column of the byte offset `byte` of `text`, columns count UTF-16 code
+/// units like SARIF's default `columnKind`
+fn position(text: &str, byte: usize) -> (usize, usize) {
+    let line_start = text[..byte].rfind('\n').map_or(0, |i| i + 1);
+    (
+        text[..byte].matches('\n').count() + 1,
+        text[line_start..byte].encode_utf16().count() + 1,
+    )
+}
+
+impl Region {
+    fn new(text: &str, range: &std::ops::Range<usize>) -> Self {
+        let (start_line, start_column) = position(text, range.start);
+        let (end_line, end_column) = position(text, range.end);
+        Region {
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: Message,
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Hint => "note",
+    }
+}
+
+fn rule(q: &AQuery) -> Rule {
+    Rule {
+        id: q.id.to_owned(),
+        short_description: Message::new(&q.name),
+        full_description: Message::new(&q.explanation),
+        help: Message::new(&q.solution),
+        default_configuration: Configuration {
+            level: level(q.severity),
+        },
+        properties: RuleProperties {
+            category: q.category,
+            maturity: q.maturity,
+        },
+    }
+}
+
+pub fn write(out: &mut impl Write, match_vec: &[AMatch]) -> io::Result<()> {
+    let mut queries: Vec<&AQuery> = QUERIES.values().collect();
+    queries.sort_by_key(|q| &q.id);
+
+    let mut sources = Sources::default();
+    let results = match_vec
+        .iter()
+        .map(|m| {
+            let uri = relative_path(&m.file);
+            let edits = sources.edits(m);
+            let text = sources.text(&m.file);
+            let fixes = edits
+                .map(|edits| Fix {
+                    description: Message::new(&m.fix),
+                    artifact_changes: vec![ArtifactChange {
+                        artifact_location: ArtifactLocation { uri: uri.clone() },
+                        replacements: edits
+                            .iter()
+                            .map(|e| Replacement {
+                                deleted_region: Region::new(text, &e.range),
+                                inserted_content: Message::new(&e.replacement),
+                            })
+                            .collect(),
+                    }],
+                })
+                .into_iter()
+                .collect();
+
+            SarifResult {
+                rule_id: m.rule.to_owned(),
+                rule_index: queries.iter().position(|q| q.id == m.rule).unwrap(),
+                level: level(m.severity),
+                message: Message::new(format!("{}: {}", m.message, m.fix)),
+                locations: vec![Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: ArtifactLocation { uri },
+                        region: Region::new(text, &m.byte_range),
+                    },
+                }],
+                fixes,
+            }
+        })
+        .collect();
+
+    let log = Log {
+        schema: SCHEMA,
+        version: "2.1.0",
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "nixpkgs-lint",
+                    version: crate_version!(),
+                    information_uri: HOMEPAGE,
+                    rules: queries.into_iter().map(rule).collect(),
+                },
+            },
+            column_kind: "utf16CodeUnits",
+            results,
+        }],
+    };
+
+    writeln!(out, "{}", serde_json::to_string_pretty(&log).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{write, Region};
+    use crate::display::lint_file;
+
+    #[test]
+    fn sarif_results() {
+        let file = "assets/simple.nix";
+        let matches = lint_file(file);
+
+        let mut out = Vec::new();
+        write(&mut out, &matches).unwrap();
+        let log: Value = serde_json::from_slice(&out).unwrap();
+        let run = &log["runs"][0];
+        let result = &run["results"][0];
+
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"][result["ruleIndex"].as_u64().unwrap() as usize]["id"],
+            "BuildTimeToolInBuildInputs"
+        );
+        assert_eq!(result["ruleId"], "BuildTimeToolInBuildInputs");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"],
+            json!({
+                "artifactLocation": { "uri": file },
+                "region": { "startLine": 5, "startColumn": 5, "endLine": 5, "endColumn": 15 }
+            })
+        );
+        // moved to nativeBuildInputs
+        assert_eq!(
+            result["fixes"][0]["artifactChanges"][0]["replacements"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn sarif_utf16_columns() {
+        let text = "{\n  description = \"𝄞é\"; buildInputs = [ cmake ];\n}";
+        let cmake = text.find("cmake").unwrap();
+        let region = Region::new(text, &(cmake..cmake + 5));
+
+        // 𝄞 is 4 bytes and 2 code units, é is 2 bytes and 1 code unit
+        assert_eq!(
+            (region.start_line, region.start_column, region.end_column),
+            (2, 40, 45)
+        );
+    }
+}
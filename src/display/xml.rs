@@ -0,0 +1,170 @@
+//! Checkstyle and JUnit XML reports, every linted file is listed, with or without matches
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use super::relative_path;
+use crate::query::{AMatch, Severity};
+
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// The files with their matches, in the order they were linted
+fn by_file<'a>(files: &'a [String], match_vec: &'a [AMatch]) -> Vec<(&'a str, Vec<&'a AMatch>)> {
+    let mut matches: HashMap<&str, Vec<&AMatch>> = HashMap::new();
+    for m in match_vec {
+        matches.entry(&m.file).or_default().push(m);
+    }
+
+    files
+        .iter()
+        .map(|file| {
+            (
+                file.as_str(),
+                matches.remove(file.as_str()).unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+fn checkstyle_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Hint => "info",
+    }
+}
+
+pub fn write_checkstyle(
+    out: &mut impl Write,
+    files: &[String],
+    match_vec: &[AMatch],
+) -> io::Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<checkstyle version="4.3">"#)?;
+    for (file, matches) in by_file(files, match_vec) {
+        writeln!(out, r#"  <file name="{}">"#, escape(&relative_path(file)))?;
+        for m in matches {
+            writeln!(
+                out,
+                r#"    <error line="{}" column="{}" severity="{}" message="{}" source="nixpkgs-lint.{}"/>"#,
+                m.line,
+                m.column,
+                checkstyle_severity(m.severity),
+                escape(&format!("{}: {}", m.message, m.fix)),
+                escape(&m.rule),
+            )?;
+        }
+        writeln!(out, "  </file>")?;
+    }
+    writeln!(out, "</checkstyle>")
+}
+
+/// Every file is a test case which fails once for every match.
+pub fn write_junit(out: &mut impl Write, files: &[String], match_vec: &[AMatch]) -> io::Result<()> {
+    let by_file = by_file(files, match_vec);
+    let tests = by_file.len();
+    let failures = by_file.iter().filter(|(_, ms)| !ms.is_empty()).count();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<testsuites name="nixpkgs-lint" tests="{tests}" failures="{failures}" errors="0">"#
+    )?;
+    writeln!(
+        out,
+        r#"  <testsuite name="nixpkgs-lint" tests="{tests}" failures="{failures}" errors="0">"#
+    )?;
+    for (file, matches) in by_file {
+        let path = escape(&relative_path(file));
+        if matches.is_empty() {
+            writeln!(
+                out,
+                r#"    <testcase classname="nixpkgs-lint" name="{path}"/>"#
+            )?;
+            continue;
+        }
+        writeln!(
+            out,
+            r#"    <testcase classname="nixpkgs-lint" name="{path}">"#
+        )?;
+        for m in matches {
+            writeln!(
+                out,
+                r#"      <failure type="{}" message="{}">{}:{}:{}: {}</failure>"#,
+                escape(&m.rule),
+                escape(&m.message),
+                path,
+                m.line,
+                m.column,
+                escape(&m.fix),
+            )?;
+        }
+        writeln!(out, "    </testcase>")?;
+    }
+    writeln!(out, "  </testsuite>")?;
+    writeln!(out, "</testsuites>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, write_checkstyle, write_junit};
+    use crate::display::lint_file;
+
+    #[test]
+    fn xml_escape() {
+        assert_eq!(
+            escape(r#"<a b="c">&'"#),
+            "&lt;a b=&quot;c&quot;&gt;&amp;&apos;"
+        );
+    }
+
+    #[test]
+    fn checkstyle() {
+        let files = ["assets/var.nix".to_string()];
+        let mut out = Vec::new();
+        write_checkstyle(&mut out, &files, &lint_file(&files[0])).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="assets/var.nix">
+    <error line="4" column="15" severity="warning" message="*Flags not a list: convert to a list" source="nixpkgs-lint.FlagsNotList"/>
+  </file>
+</checkstyle>
+"#
+        );
+    }
+
+    #[test]
+    fn junit() {
+        let files = [
+            "assets/var.nix".to_string(),
+            "assets/depth/simple.nix".to_string(),
+        ];
+        let mut out = Vec::new();
+        write_junit(&mut out, &files, &lint_file(&files[0])).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="nixpkgs-lint" tests="2" failures="1" errors="0">
+  <testsuite name="nixpkgs-lint" tests="2" failures="1" errors="0">
+    <testcase classname="nixpkgs-lint" name="assets/var.nix">
+      <failure type="FlagsNotList" message="*Flags not a list">assets/var.nix:4:15: convert to a list</failure>
+    </testcase>
+    <testcase classname="nixpkgs-lint" name="assets/depth/simple.nix"/>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
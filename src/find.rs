@@ -19,7 +19,9 @@ fn is_nix_file(entry: &DirEntry) -> bool {
 }
 
 pub fn find_nix_files(path: &PathBuf) -> Vec<String> {
+    // sorted for reproducible reports
     WalkDir::new(path)
+        .sort_by_file_name()
         .into_iter()
         .filter_entry(|e| !is_hidden(e))
         .map_while(Result::ok)
@@ -30,7 +32,7 @@ pub fn find_nix_files(path: &PathBuf) -> Vec<String> {
         .filter(|path| !path.path_is_symlink())
         // 'pkgs/test/nixpkgs-check-by-name/tests/package-nix-dir/pkgs/by-name/fo/foo/package.nix'
         // is a directory.
-        .filter(|path| (!path.metadata().unwrap().is_dir()))
+        .filter(|path| !path.metadata().unwrap().is_dir())
         .map(|f| f.path().to_str().unwrap().to_owned())
         .collect()
 }
@@ -1,6 +1,7 @@
 use predicates::prelude::*;
 
 use crate::query::{AMatch, AQuery, QueryType};
+use crate::suppression::find_suppressions;
 
 use tree_sitter::{QueryCursor, Tree};
 
@@ -27,7 +28,7 @@ fn print_tree(path: &str, tree: &Tree, text: &str) {
     }
 }
 
-fn get_tree(text: &str) -> Tree {
+pub fn get_tree(text: &str) -> Tree {
     let mut parser = tree_sitter::Parser::new();
     parser
         .set_language(tree_sitter_nix::language())
@@ -51,6 +52,11 @@ pub fn find_lints(path: &str, text: &str, queries: &Vec<AQuery>, printtree: &boo
     let mut whole_text = String::new();
 
     for q in queries {
+        // reported after the other lints, see below
+        if q.type_of_query == QueryType::UnusedSuppression {
+            continue;
+        }
+
         let query =
             tree_sitter::Query::new(tree_sitter_nix::language(), &q.query_string()).unwrap();
 
@@ -58,6 +64,16 @@ pub fn find_lints(path: &str, text: &str, queries: &Vec<AQuery>, printtree: &boo
 
         for qm in QueryCursor::new().matches(&query, tree.root_node(), text.as_bytes()) {
             let mut list_range: std::ops::Range<usize> = 0..0;
+            let captures: Vec<(String, std::ops::Range<usize>)> = qm
+                .captures
+                .iter()
+                .map(|c| {
+                    (
+                        query.capture_names()[c.index as usize].to_owned(),
+                        c.node.byte_range(),
+                    )
+                })
+                .collect();
 
             if let Some(node) = qm.nodes_for_capture_index(capture_id).next() {
                 let cursor = &mut node.walk();
@@ -69,17 +85,20 @@ pub fn find_lints(path: &str, text: &str, queries: &Vec<AQuery>, printtree: &boo
                         continue;
                     }
                     let match_to_push = |matched| AMatch {
+                        rule: q.id.to_owned(),
                         file: path.to_owned(),
                         message: q.name.to_owned(),
                         matched,
                         fix: q.solution.to_owned(),
                         type_of_fix: q.type_of_fix.to_owned(),
+                        severity: q.severity,
                         line: n.start_position().row + 1,
-                        //end_line: n.end_position().row + 1,
+                        end_line: n.end_position().row + 1,
                         column: n.start_position().column + 1,
                         end_column: n.end_position().column + 1,
                         byte_range: n.byte_range(),
                         list_byte_range: list_range.to_owned(),
+                        captures: captures.to_owned(),
                         query: q.to_owned(),
                     };
                     match q.type_of_query {
@@ -93,12 +112,11 @@ pub fn find_lints(path: &str, text: &str, queries: &Vec<AQuery>, printtree: &boo
                             }
                             _ => {}
                         },
-                        QueryType::String => match n.kind() {
-                            "string_expression" => {
+                        QueryType::String => {
+                            if n.kind() == "string_expression" {
                                 match_vec.push(match_to_push(text_from_node(&n, text)));
                             }
-                            _ => {}
-                        },
+                        }
                         QueryType::BindingAStringInsteadOfList => {
                             match n.kind() {
                                 "binding" => {
@@ -133,44 +151,54 @@ pub fn find_lints(path: &str, text: &str, queries: &Vec<AQuery>, printtree: &boo
                             }
                             _ => {}
                         },
-                        QueryType::RecursiveAttrSet => match n.kind() {
-                            "rec_attrset_expression" => {
+                        QueryType::RecursiveAttrSet => {
+                            if n.kind() == "rec_attrset_expression" {
                                 match_vec.push(match_to_push(text_from_node(&n, text)));
                             }
-                            _ => {}
-                        },
-                        QueryType::AttrNameInFunction => match n.kind() {
-                            "identifier" => {
+                        }
+                        QueryType::AttrNameInFunction => {
+                            if n.kind() == "identifier" {
                                 match_vec.push(match_to_push(text_from_node(&n, text)));
                             }
-                            _ => {}
-                        },
-                        QueryType::BindingWithExpression => match n.kind() {
-                            "with_expression" => {
+                        }
+                        QueryType::BindingWithExpression => {
+                            if n.kind() == "with_expression" {
                                 match_vec.push(match_to_push(text_from_node(&n, text)));
                                 // we only want the first apply_expression
                                 break;
                             }
-                            _ => {}
-                        },
-                        QueryType::AttrValueInContext => match n.kind() {
-                            "string_fragment" => {
+                        }
+                        QueryType::AttrValueInContext => {
+                            if n.kind() == "string_fragment" {
                                 match_vec.push(match_to_push(text_from_node(&n, text)));
                             }
-                            _ => {}
-                        },
+                        }
+                        QueryType::UnusedSuppression => {}
                     }
                 }
             }
         }
     }
+
+    let mut suppressions = find_suppressions(&tree, text);
+    match_vec.retain(|m| !suppressions.iter_mut().any(|s| s.suppresses(m)));
+
+    if let Some(q) = queries
+        .iter()
+        .find(|q| q.type_of_query == QueryType::UnusedSuppression)
+    {
+        for s in &suppressions {
+            match_vec.extend(s.unused(path, text, queries, q));
+        }
+    }
+
     match_vec
 }
 
 #[cfg(test)]
 mod tests {
     use crate::queries::QUERIES;
-    use crate::queries::UNFINISHED_QUERIES;
+    use crate::query::Severity::*;
     use crate::query::TypeOfFix::*;
     use crate::{
         queries::add_default_queries,
@@ -198,29 +226,45 @@ mod tests {
 
         let expected = [
             (AMatch {
+                rule: "BuildTimeToolInBuildInputs".to_string(),
                 file: "".to_string(),
                 message: "build time tool in buildInputs".to_string(),
                 matched: "pkg-config".to_string(),
                 fix: "move this from buildInputs to nativeBuildInputs".to_string(),
                 type_of_fix: Move,
+                severity: Warning,
                 line: 5,
+                end_line: 5,
                 column: 17,
                 end_column: 27,
                 byte_range: 112..122,
                 list_byte_range: 94..160,
+                captures: vec![
+                    ("q".to_string(), 80..161),
+                    ("a".to_string(), 80..91),
+                    ("l".to_string(), 94..160),
+                ],
                 query: QUERIES.get("BuildTimeToolInBuildInputs").unwrap().clone(),
             }),
             (AMatch {
+                rule: "BuildTimeToolInBuildInputs".to_string(),
                 file: "".to_string(),
                 message: "build time tool in buildInputs".to_string(),
                 matched: "cmake".to_string(),
                 fix: "move this from buildInputs to nativeBuildInputs".to_string(),
                 type_of_fix: Move,
+                severity: Warning,
                 line: 6,
+                end_line: 6,
                 column: 17,
                 end_column: 22,
                 byte_range: 139..144,
                 list_byte_range: 94..160,
+                captures: vec![
+                    ("q".to_string(), 80..161),
+                    ("a".to_string(), 80..91),
+                    ("l".to_string(), 94..160),
+                ],
                 query: QUERIES.get("BuildTimeToolInBuildInputs").unwrap().clone(),
             }),
         ];
@@ -246,17 +290,21 @@ mod tests {
         let result = find_lints("", &expr, &queries, &false);
 
         let expected = [
-            (AMatch { 
-                file: "".to_string(), 
+            (AMatch {
+                rule: "UnnormalizedPythonPname".to_string(),
+                file: "".to_string(),
                 message: "unnormalized python pname".to_string(), 
                 matched: "unnormalized_pname".to_string(),
                 fix: "normalize this according to PEP503, for example, lowercase and use `-` instead of `.` and `_`".to_string(),
                 type_of_fix: Change,
+                severity: Warning,
                 line: 4,
+                end_line: 4,
                 column: 24,
                 end_column: 42,
                 byte_range: 96..114,
                 list_byte_range: 0..0,
+                captures: vec![("f".to_string(), 48..66), ("a".to_string(), 87..92), ("q".to_string(), 96..114)],
                 query: QUERIES.get("UnnormalizedPythonPname").unwrap().clone(),
             }),
         ];
@@ -282,21 +330,27 @@ mod tests {
         add_unfinished_queries(&mut queries);
         let result = find_lints("", &expr, &queries, &false);
 
-        let expected = [
-            (AMatch { 
-                file: "".to_string(), 
-                message: "refs/tags with rev".to_string(), 
-                matched: "refs/tags/".to_string(),
-                fix: "replace rev with tag and remove the prefix, refs/tags/".to_string(),
-                type_of_fix: Change,
-                line: 7,
-                column: 24,
-                end_column: 34,
-                byte_range: 188..198,
-                list_byte_range: 0..0,
-                query: UNFINISHED_QUERIES.get("RefsTagsWithRev").unwrap().clone(),
-            }),
-        ];
+        let expected = [(AMatch {
+            rule: "RefsTagsWithRev".to_string(),
+            file: "".to_string(),
+            message: "refs/tags with rev".to_string(),
+            matched: "refs/tags/".to_string(),
+            fix: "replace rev with tag and remove the prefix, refs/tags/".to_string(),
+            type_of_fix: Change,
+            severity: Warning,
+            line: 7,
+            end_line: 7,
+            column: 24,
+            end_column: 34,
+            byte_range: 188..198,
+            list_byte_range: 0..0,
+            captures: vec![
+                ("f".to_string(), 84..99),
+                ("a".to_string(), 181..184),
+                ("q".to_string(), 188..198),
+            ],
+            query: QUERIES.get("RefsTagsWithRev").unwrap().clone(),
+        })];
 
         assert_eq!(result, expected)
     }
@@ -319,18 +373,22 @@ mod tests {
         let result = find_lints("", &expr, &queries, &false);
 
         let expected = [
-            (AMatch { 
-                file: "".to_string(), 
+            (AMatch {
+                rule: "BindingWithExpression".to_string(),
+                file: "".to_string(),
                 message: "binding with expression".to_string(), 
                 matched: "with lib; {\n                description = \"\";\n                homepage = \"\";\n                platforms = platforms.unix;\n              }".to_string(),
                 fix: "remove with expression".to_string(),
                 type_of_fix: Change,
+                severity: Warning,
                 line: 4,
+                end_line: 8,
                 column: 22,
                 end_column: 16,
                 byte_range: 85..221,
                 list_byte_range: 0..0,
-                query: UNFINISHED_QUERIES.get("BindingWithExpression").unwrap().clone(),
+                captures: vec![("q".to_string(), 78..222), ("a".to_string(), 78..82), ("e".to_string(), 90..93)],
+                query: QUERIES.get("BindingWithExpression").unwrap().clone(),
             }),
         ];
 
@@ -0,0 +1,844 @@
+use std::{collections::BTreeMap, fs::read_to_string, mem::take, ops::Range};
+
+use rayon::prelude::*;
+use tree_sitter::{Node, Tree};
+
+use crate::find_lints::get_tree;
+use crate::query::{AMatch, QueryType, TypeOfFix};
+
+/// Replaces `range` of the linted text with `replacement`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+#[derive(Debug)]
+pub struct FileFix {
+    pub file: String,
+    pub original: String,
+    pub fixed: String,
+    pub applied: Vec<AMatch>,
+    /// matches that could not be fixed and why
+    pub skipped: Vec<(AMatch, String)>,
+}
+
+impl FileFix {
+    pub fn is_changed(&self) -> bool {
+        self.original != self.fixed
+    }
+}
+
+#[derive(Debug)]
+pub struct FixedText {
+    pub text: String,
+    pub applied: Vec<AMatch>,
+    pub skipped: Vec<(AMatch, String)>,
+}
+
+/// The edits fixing one match.
+#[derive(Debug)]
+struct MatchFix {
+    edits: Vec<Edit>,
+    /// set when moving to a binding which doesn't exist yet, it's created once for all matches
+    new_binding: Option<NewBinding>,
+}
+
+impl From<Vec<Edit>> for MatchFix {
+    fn from(edits: Vec<Edit>) -> Self {
+        MatchFix {
+            edits,
+            new_binding: None,
+        }
+    }
+}
+
+/// A list binding to insert after the binding at `at`.
+#[derive(Debug)]
+struct NewBinding {
+    at: usize,
+    name: String,
+    /// `None` when the binding shares its line with other code, the new one goes on that line
+    indent: Option<String>,
+    /// `None` for a list on one line
+    element_indent: Option<String>,
+    elements: Vec<String>,
+}
+
+impl NewBinding {
+    fn to_edit(&self) -> Edit {
+        let list = match &self.element_indent {
+            Some(element_indent) => format!(
+                "[\n{}\n{}]",
+                self.elements
+                    .iter()
+                    .map(|e| format!("{element_indent}{e}"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                self.indent.as_deref().unwrap_or_default()
+            ),
+            None => format!("[ {} ]", self.elements.join(" ")),
+        };
+        Edit {
+            range: self.at..self.at,
+            replacement: match &self.indent {
+                Some(indent) => format!("\n{indent}{} = {list};", self.name),
+                None => format!(" {} = {list};", self.name),
+            },
+        }
+    }
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+pub fn apply_edits(text: &str, edits: &[Edit]) -> String {
+    let mut edits = edits.to_vec();
+    // stable, so insertions at the same position keep their order
+    edits.sort_by_key(|e| (e.range.start, e.range.end));
+
+    let mut fixed = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for e in edits {
+        fixed.push_str(&text[cursor..e.range.start]);
+        fixed.push_str(&e.replacement);
+        cursor = e.range.end;
+    }
+    fixed.push_str(&text[cursor..]);
+    fixed
+}
+
+/// The range to delete when removing `node`, the whole line if the node is alone on it.
+/// A trailing comment on that line is assumed to be about the node and goes with it.
+fn removal_range(node: &Node, text: &str) -> Range<usize> {
+    let Range { start, end } = node.byte_range();
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[end..].find('\n').map_or(text.len(), |i| end + i + 1);
+
+    let rest = text[end..line_end].trim();
+    if text[line_start..start].trim().is_empty() && (rest.is_empty() || rest.starts_with('#')) {
+        return line_start..line_end;
+    }
+
+    node.prev_sibling().map_or(start, |p| p.end_byte())..end
+}
+
+/// Finds the list element which the match points at.
+///
+/// Identifiers nested deeper in the list, e.g. `ps.wheel` in `(python.withPackages (ps: [ ps.wheel ]))`,
+/// are matched too but can't be touched without understanding the surrounding expression.
+fn list_element<'t>(m: &AMatch, tree: &'t Tree) -> Result<Node<'t>, String> {
+    let node = tree
+        .root_node()
+        .named_descendant_for_byte_range(m.byte_range.start, m.byte_range.end)
+        .ok_or("the matched node no longer exists")?;
+
+    match node.parent() {
+        Some(element)
+            if element.kind() == "variable_expression"
+                && element
+                    .parent()
+                    .is_some_and(|list| list.byte_range() == m.list_byte_range) =>
+        {
+            Ok(element)
+        }
+        _ => Err(String::from("not a direct element of the list")),
+    }
+}
+
+/// The whitespace before `node` on its line.
+fn indentation(node: &Node, text: &str) -> String {
+    let start = node.start_byte();
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    text[line_start..start]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect()
+}
+
+/// Whether only whitespace is before `node` on its line.
+fn starts_line(node: &Node, text: &str) -> bool {
+    let start = node.start_byte();
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    text[line_start..start].trim().is_empty()
+}
+
+fn ancestor<'t>(node: &Node<'t>, kind: &str) -> Option<Node<'t>> {
+    let mut parent = node.parent();
+    while let Some(p) = parent {
+        if p.kind() == kind {
+            return Some(p);
+        }
+        parent = p.parent();
+    }
+    None
+}
+
+/// The lists which are the binding's expression or an operand of its top level `++` chain,
+/// their elements are unconditional and not in a `with` scope.
+fn binding_lists<'t>(binding: &Node<'t>) -> Vec<Node<'t>> {
+    let mut lists = Vec::new();
+    let mut operands: Vec<Node> = binding
+        .child_by_field_name("expression")
+        .into_iter()
+        .collect();
+    while let Some(expression) = operands.pop() {
+        match expression.kind() {
+            "list_expression" => lists.push(expression),
+            "binary_expression"
+                if expression
+                    .child_by_field_name("operator")
+                    .is_some_and(|o| o.kind() == "++") =>
+            {
+                operands.extend(expression.child_by_field_name("right"));
+                operands.extend(expression.child_by_field_name("left"));
+            }
+            _ => {}
+        }
+    }
+    lists
+}
+
+/// The first list of a binding's expression, e.g. `[ ... ]` of `[ ... ] ++ ...`.
+fn binding_list<'t>(binding: &Node<'t>) -> Option<Node<'t>> {
+    binding_lists(binding).into_iter().next()
+}
+
+/// Inserts `element` before the closing bracket of `list`, following the list's layout.
+fn insert_into_list(list: &Node, element: &str, text: &str) -> Edit {
+    let close = list.end_byte() - 1;
+    let elements: Vec<Node> = list
+        .named_children(&mut list.walk())
+        .filter(|n| n.kind() != "comment")
+        .collect();
+
+    let replacement = match elements.last() {
+        // the closing bracket is on its own line
+        _ if text[..close].trim_end_matches([' ', '\t']).ends_with('\n') => {
+            let indent = elements.first().map_or_else(
+                || format!("{}  ", indentation(list, text)),
+                |first| indentation(first, text),
+            );
+            let at = text[..close].rfind('\n').unwrap() + 1;
+            return Edit {
+                range: at..at,
+                replacement: format!("{indent}{element}\n"),
+            };
+        }
+        Some(last) => {
+            return Edit {
+                range: last.end_byte()..last.end_byte(),
+                replacement: format!(" {element}"),
+            };
+        }
+        None if text[..close].ends_with(char::is_whitespace) => format!("{element} "),
+        None => format!(" {element} "),
+    };
+
+    Edit {
+        range: close..close,
+        replacement,
+    }
+}
+
+/// Moves the element to the query's destination binding in the same attrset.
+fn move_to_destination(m: &AMatch, text: &str, tree: &Tree) -> Result<MatchFix, String> {
+    let destination = &m.query.destination;
+    if destination.is_empty() {
+        return Err(String::from("the lint doesn't declare where to move to"));
+    }
+
+    let element = list_element(m, tree)?;
+    let name = &text[element.byte_range()];
+    let list = element.parent().unwrap();
+    let binding = ancestor(&list, "binding").ok_or("the list isn't bound to an attribute")?;
+    if !binding_lists(&binding).contains(&list) {
+        return Err(String::from(
+            "the list is conditional or in a scope like `with`, move this by hand",
+        ));
+    }
+    let binding_set = binding.parent().unwrap();
+
+    let removal = Edit {
+        range: removal_range(&element, text),
+        replacement: String::new(),
+    };
+
+    let inherited = binding_set
+        .named_children(&mut binding_set.walk())
+        .filter(|b| matches!(b.kind(), "inherit" | "inherit_from"))
+        .filter_map(|b| b.child_by_field_name("attrs"))
+        .any(|attrs| {
+            attrs
+                .named_children(&mut attrs.walk())
+                .any(|a| &text[a.byte_range()] == destination)
+        });
+    if inherited {
+        return Err(format!("`{destination}` is inherited, move this by hand"));
+    }
+
+    let existing = binding_set
+        .named_children(&mut binding_set.walk())
+        .find(|b| {
+            b.kind() == "binding"
+                && b.child_by_field_name("attrpath")
+                    .is_some_and(|a| &text[a.byte_range()] == destination)
+        });
+
+    if let Some(existing) = existing {
+        let destination_list =
+            binding_list(&existing).ok_or(format!("`{destination}` isn't a list"))?;
+
+        let already_there = destination_list
+            .named_children(&mut destination_list.walk())
+            .any(|e| &text[e.byte_range()] == name);
+        if already_there {
+            return Ok(vec![removal].into());
+        }
+
+        return Ok(vec![removal, insert_into_list(&destination_list, name, text)].into());
+    }
+
+    let multiline = list.start_position().row != list.end_position().row;
+    Ok(MatchFix {
+        edits: vec![removal],
+        new_binding: Some(NewBinding {
+            at: binding.end_byte(),
+            name: destination.to_owned(),
+            indent: starts_line(&binding, text).then(|| indentation(&binding, text)),
+            element_indent: multiline.then(|| indentation(&element, text)),
+            elements: vec![name.to_owned()],
+        }),
+    })
+}
+
+/// Splits a string into a list with one string per word, interpolations stay in their word.
+///
+/// Quotes and escapes are refused, `"--with-foo='a b'"` is one shell word but not one Nix word.
+fn convert_to_list(m: &AMatch, text: &str, tree: &Tree) -> Result<Edit, String> {
+    let string = tree
+        .root_node()
+        .named_descendant_for_byte_range(m.byte_range.start, m.byte_range.end)
+        .filter(|n| n.kind() == "string_expression")
+        .ok_or("the matched string no longer exists")?;
+
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut end_word = |word: &mut String| {
+        if !word.is_empty() {
+            words.push(format!("\"{}\"", take(word)));
+        }
+    };
+
+    for part in string.named_children(&mut string.walk()) {
+        let part_text = &text[part.byte_range()];
+        match part.kind() {
+            "string_fragment" => {
+                for c in part_text.chars() {
+                    match c {
+                        '\'' | '"' => {
+                            return Err(String::from(
+                                "the string contains quotes, splitting it into words is ambiguous",
+                            ))
+                        }
+                        c if c.is_whitespace() => end_word(&mut word),
+                        c => word.push(c),
+                    }
+                }
+            }
+            "escape_sequence" if matches!(part_text, "\\n" | "\\t") => end_word(&mut word),
+            "interpolation" => word.push_str(part_text),
+            _ => {
+                return Err(format!(
+                    "the string contains `{part_text}`, splitting it into words is ambiguous"
+                ))
+            }
+        }
+    }
+    end_word(&mut word);
+
+    let list = if words.is_empty() {
+        String::from("[ ]")
+    } else if string.start_position().row != string.end_position().row {
+        let indent = indentation(&string, text);
+        format!(
+            "[\n{}\n{indent}]",
+            words
+                .iter()
+                .map(|w| format!("{indent}  {w}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    } else {
+        format!("[ {} ]", words.join(" "))
+    };
+
+    Ok(Edit {
+        range: string.byte_range(),
+        replacement: list,
+    })
+}
+
+/// Renames the function of `lib.optional cond [ ... ]` to `optionals`,
+/// also when it's written as `optional` or `lib.lists.optional`.
+fn optional_to_optionals(m: &AMatch, text: &str, tree: &Tree) -> Result<Edit, String> {
+    let function = tree
+        .root_node()
+        .named_descendant_for_byte_range(m.byte_range.start, m.byte_range.end)
+        .filter(|n| n.kind() == "apply_expression")
+        .and_then(|n| n.child_by_field_name("function"))
+        .and_then(|n| n.child_by_field_name("function"))
+        .ok_or("the matched expression no longer exists")?;
+
+    let name = match function.kind() {
+        "variable_expression" => function.child_by_field_name("name"),
+        "select_expression" => function
+            .child_by_field_name("attrpath")
+            .and_then(|a| a.named_children(&mut a.walk()).last()),
+        _ => None,
+    }
+    .filter(|n| &text[n.byte_range()] == "optional")
+    .ok_or("the function isn't lib.optional")?;
+
+    Ok(Edit {
+        range: name.byte_range(),
+        replacement: String::from("optionals"),
+    })
+}
+
+/// Applies the query's rewrites to the captures of the match.
+fn rewrite_captures(m: &AMatch, text: &str) -> Result<Vec<Edit>, String> {
+    let capture = |name: &str| {
+        m.captures
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, range)| range.to_owned())
+    };
+
+    m.query
+        .rewrites
+        .iter()
+        .map(|rewrite| {
+            Ok(Edit {
+                range: capture(&rewrite.capture)
+                    .ok_or(format!("@{} wasn't captured", rewrite.capture))?,
+                replacement: rewrite.render(|name| capture(name).map(|r| &text[r]))?,
+            })
+        })
+        .collect()
+}
+
+fn edits_for_match(m: &AMatch, text: &str, tree: &Tree) -> Result<MatchFix, String> {
+    match m.type_of_fix {
+        TypeOfFix::Remove if m.query.type_of_query == QueryType::UnusedSuppression => {
+            let comment = tree
+                .root_node()
+                .descendant_for_byte_range(m.byte_range.start, m.byte_range.end)
+                .filter(|n| n.kind() == "comment" && n.byte_range() == m.byte_range)
+                .ok_or("the comment names other lints too, remove this one by hand")?;
+            Ok(vec![Edit {
+                range: removal_range(&comment, text),
+                replacement: String::new(),
+            }]
+            .into())
+        }
+        TypeOfFix::Remove => {
+            let element = list_element(m, tree)?;
+            Ok(vec![Edit {
+                range: removal_range(&element, text),
+                replacement: String::new(),
+            }]
+            .into())
+        }
+        TypeOfFix::Move => move_to_destination(m, text, tree),
+        TypeOfFix::ConvertToList => Ok(vec![convert_to_list(m, text, tree)?].into()),
+        TypeOfFix::Change if !m.query.rewrites.is_empty() => Ok(rewrite_captures(m, text)?.into()),
+        TypeOfFix::Change => match m.query.type_of_query {
+            QueryType::ArgToOptionalAList => Ok(vec![optional_to_optionals(m, text, tree)?].into()),
+            _ => Err(String::from("no automatic fix available")),
+        },
+    }
+}
+
+/// The edits fixing `m` on its own, for reporters which suggest fixes instead of applying them.
+/// `text` and `tree` are the trimmed contents of the file `m` was found in.
+pub fn match_edits(m: &AMatch, text: &str, tree: &Tree) -> Result<Vec<Edit>, String> {
+    let fix = edits_for_match(m, text, tree)?;
+    let mut edits = fix.edits;
+    edits.extend(fix.new_binding.as_ref().map(NewBinding::to_edit));
+    Ok(edits)
+}
+
+/// Fixes the matches of one file, matches whose edits would overlap earlier ones are skipped.
+pub fn fix_text(text: &str, matches: Vec<AMatch>) -> FixedText {
+    let tree = get_tree(text);
+    let mut edits: Vec<Edit> = Vec::new();
+    let mut new_bindings: Vec<NewBinding> = Vec::new();
+    let mut applied = Vec::new();
+    let mut skipped = Vec::new();
+
+    for m in matches {
+        match edits_for_match(&m, text, &tree) {
+            // several matches of one query match share their rewrites
+            Ok(fix) if !fix.edits.is_empty() && fix.edits.iter().all(|n| edits.contains(n)) => {
+                applied.push(m);
+            }
+            Ok(fix)
+                if fix
+                    .edits
+                    .iter()
+                    .any(|n| edits.iter().any(|e| overlaps(&e.range, &n.range))) =>
+            {
+                skipped.push((m, String::from("overlaps with another fix")));
+            }
+            Ok(fix) => {
+                edits.extend(fix.edits);
+                if let Some(new) = fix.new_binding {
+                    match new_bindings
+                        .iter_mut()
+                        .find(|b| b.at == new.at && b.name == new.name)
+                    {
+                        Some(b) if b.elements.contains(&new.elements[0]) => {}
+                        Some(b) => b.elements.extend(new.elements),
+                        None => new_bindings.push(new),
+                    }
+                }
+                applied.push(m);
+            }
+            Err(reason) => skipped.push((m, reason)),
+        }
+    }
+
+    edits.extend(new_bindings.iter().map(NewBinding::to_edit));
+
+    let fixed = apply_edits(text, &edits);
+
+    if !tree.root_node().has_error() && get_tree(&fixed).root_node().has_error() {
+        skipped.extend(
+            applied
+                .drain(..)
+                .map(|m| (m, String::from("the fixed file doesn't parse"))),
+        );
+        return FixedText {
+            text: text.to_owned(),
+            applied,
+            skipped,
+        };
+    }
+
+    FixedText {
+        text: fixed,
+        applied,
+        skipped,
+    }
+}
+
+fn fix_file(file: String, matches: Vec<AMatch>) -> FileFix {
+    let original = read_to_string(&file).unwrap();
+    // the matches were found in the trimmed contents
+    let start = original.len() - original.trim_start().len();
+    let text = original.trim();
+    let end = start + text.len();
+
+    let fixed_text = fix_text(text, matches);
+    let fixed = format!(
+        "{}{}{}",
+        &original[..start],
+        fixed_text.text,
+        &original[end..]
+    );
+
+    FileFix {
+        file,
+        original,
+        fixed,
+        applied: fixed_text.applied,
+        skipped: fixed_text.skipped,
+    }
+}
+
+pub fn fix_matches(match_vec: Vec<AMatch>) -> Vec<FileFix> {
+    let mut by_file: BTreeMap<String, Vec<AMatch>> = BTreeMap::new();
+    for m in match_vec {
+        by_file.entry(m.file.clone()).or_default().push(m);
+    }
+
+    by_file
+        .into_par_iter()
+        .map(|(file, matches)| fix_file(file, matches))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        find_lints::find_lints,
+        queries::{add_default_queries, add_unfinished_queries},
+        query::AQuery,
+    };
+
+    use super::fix_text;
+
+    fn fix(expr: &str) -> String {
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_default_queries(&mut queries);
+        add_unfinished_queries(&mut queries);
+        fix_text(expr, find_lints("", expr, &queries, &false)).text
+    }
+
+    #[test]
+    fn fix_remove_multiline() {
+        let expr = "{ buildPythonPackage, pytest-runner, flake8 }:
+
+            buildPythonPackage {
+              nativeCheckInputs = [
+                pytestCheckHook
+                pytest-runner
+                flake8 # linting
+              ];
+            }";
+
+        let expected = "{ buildPythonPackage, pytest-runner, flake8 }:
+
+            buildPythonPackage {
+              nativeCheckInputs = [
+                pytestCheckHook
+              ];
+            }";
+
+        assert_eq!(fix(expr), expected)
+    }
+
+    #[test]
+    fn fix_remove_inline() {
+        let expr = "{ buildPythonPackage, pytest-runner, flake8 }:
+
+            buildPythonPackage {
+              nativeCheckInputs = [ flake8 pytestCheckHook pytest-runner ];
+            }";
+
+        let expected = "{ buildPythonPackage, pytest-runner, flake8 }:
+
+            buildPythonPackage {
+              nativeCheckInputs = [ pytestCheckHook ];
+            }";
+
+        assert_eq!(fix(expr), expected)
+    }
+
+    #[test]
+    fn fix_remove_skips_nested() {
+        let expr = "{ buildPythonPackage, python }:
+
+            buildPythonPackage {
+              nativeCheckInputs = [ (python.withPackages (ps: [ ps.flake8 ])) ];
+            }";
+
+        assert_eq!(fix(expr), expr)
+    }
+
+    #[test]
+    fn fix_move_creates_destination() {
+        let expr = "{ stdenv, pkg-config, cmake, zlib }:
+
+            stdenv.mkDerivation {
+              buildInputs = [
+                pkg-config
+                zlib
+                cmake
+              ];
+            }";
+
+        let expected = "{ stdenv, pkg-config, cmake, zlib }:
+
+            stdenv.mkDerivation {
+              buildInputs = [
+                zlib
+              ];
+              nativeBuildInputs = [
+                pkg-config
+                cmake
+              ];
+            }";
+
+        assert_eq!(fix(expr), expected)
+    }
+
+    #[test]
+    fn fix_move_to_existing_destination() {
+        let expr = "{ stdenv, pkg-config, cmake, zlib }:
+
+            stdenv.mkDerivation {
+              nativeBuildInputs = [
+                ninja
+                cmake
+              ] ++ lib.optionals stdenv.isDarwin [ xcbuild ];
+
+              buildInputs = [ cmake zlib pkg-config ];
+            }";
+
+        let expected = "{ stdenv, pkg-config, cmake, zlib }:
+
+            stdenv.mkDerivation {
+              nativeBuildInputs = [
+                ninja
+                cmake
+                pkg-config
+              ] ++ lib.optionals stdenv.isDarwin [ xcbuild ];
+
+              buildInputs = [ zlib ];
+            }";
+
+        assert_eq!(fix(expr), expected)
+    }
+
+    #[test]
+    fn fix_move_on_one_line() {
+        assert_eq!(
+            fix("stdenv.mkDerivation { buildInputs = [ cmake ]; }"),
+            "stdenv.mkDerivation { buildInputs = [ ]; nativeBuildInputs = [ cmake ]; }"
+        )
+    }
+
+    #[test]
+    fn fix_move_skips_nested_lists() {
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_default_queries(&mut queries);
+
+        for expr in [
+            "stdenv.mkDerivation { buildInputs = [ zlib ] ++ lib.optionals stdenv.isDarwin [ cmake ]; }",
+            "stdenv.mkDerivation { buildInputs = with pkgs; [ zlib pkg-config ]; }",
+        ] {
+            let fixed = fix_text(expr, find_lints("", expr, &queries, &false));
+            assert_eq!(fixed.text, expr);
+            assert_eq!(
+                fixed.skipped[0].1,
+                "the list is conditional or in a scope like `with`, move this by hand"
+            );
+        }
+    }
+
+    #[test]
+    fn fix_move_skips_inherited_destination() {
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_default_queries(&mut queries);
+
+        for expr in [
+            "stdenv.mkDerivation { inherit nativeBuildInputs; buildInputs = [ cmake ]; }",
+            "stdenv.mkDerivation { inherit (old) nativeBuildInputs; buildInputs = [ cmake ]; }",
+        ] {
+            let fixed = fix_text(expr, find_lints("", expr, &queries, &false));
+            assert_eq!(fixed.text, expr);
+            assert_eq!(
+                fixed.skipped[0].1,
+                "`nativeBuildInputs` is inherited, move this by hand"
+            );
+        }
+    }
+
+    #[test]
+    fn fix_remove_redundant_package() {
+        let expr = r#"buildPythonPackage { nativeBuildInputs = [ pythonRelaxDepsHook ]; pythonRelaxDeps = [ "numpy" ]; }"#;
+        let expected =
+            r#"buildPythonPackage { nativeBuildInputs = [ ]; pythonRelaxDeps = [ "numpy" ]; }"#;
+
+        assert_eq!(fix(expr), expected)
+    }
+
+    #[test]
+    fn fix_convert_to_list() {
+        let expr = r#"{ stdenv }:
+
+            stdenv.mkDerivation {
+              configureFlags = "--enable-foo  --with-bar=${lib.getDev bar}/lib --prefix=${placeholder "out"}";
+            }"#;
+
+        let expected = r#"{ stdenv }:
+
+            stdenv.mkDerivation {
+              configureFlags = [ "--enable-foo" "--with-bar=${lib.getDev bar}/lib" "--prefix=${placeholder "out"}" ];
+            }"#;
+
+        assert_eq!(fix(expr), expected)
+    }
+
+    #[test]
+    fn fix_convert_to_list_refuses_quotes() {
+        let expr = r#"{ stdenv }:
+
+            stdenv.mkDerivation {
+              makeFlags = "CC='gcc -O2'";
+            }"#;
+
+        assert_eq!(fix(expr), expr)
+    }
+
+    #[test]
+    fn fix_optional_to_optionals() {
+        let expr = "{ stdenv, lib }:
+
+            stdenv.mkDerivation {
+              buildInputs = lib.optional stdenv.isLinux [
+                (lib.lists.optional withFoo [ foo ])
+                bar
+              ] ++ lib.optional withBaz baz;
+              propagatedBuildInputs = with lib; optional withFoo [ foo ];
+            }";
+
+        let expected = "{ stdenv, lib }:
+
+            stdenv.mkDerivation {
+              buildInputs = lib.optionals stdenv.isLinux [
+                (lib.lists.optionals withFoo [ foo ])
+                bar
+              ] ++ lib.optional withBaz baz;
+              propagatedBuildInputs = with lib; optionals withFoo [ foo ];
+            }";
+
+        assert_eq!(fix(expr), expected)
+    }
+
+    #[test]
+    fn fix_rewrites() {
+        let expr = r#"{ buildPythonPackage, fetchFromGitHub }:
+
+            buildPythonPackage rec {
+              format = "pyproject";
+
+              src = fetchFromGitHub {
+                owner = "test";
+                repo = "test";
+                rev = "refs/tags/v${version}";
+                sha256 = "";
+              };
+            }"#;
+
+        let expected = r#"{ buildPythonPackage, fetchFromGitHub }:
+
+            buildPythonPackage rec {
+              pyproject = true;
+
+              src = fetchFromGitHub {
+                owner = "test";
+                repo = "test";
+                tag = "v${version}";
+                hash = "";
+              };
+            }"#;
+
+        assert_eq!(fix(expr), expected)
+    }
+
+    #[test]
+    fn fix_setuptools_format_is_skipped() {
+        let expr = r#"buildPythonPackage { format = "setuptools"; }"#;
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_unfinished_queries(&mut queries);
+        let fixed = fix_text(expr, find_lints("", expr, &queries, &false));
+
+        assert_eq!(fixed.text, expr);
+        assert_eq!(fixed.skipped.len(), 1);
+        assert_eq!(fixed.skipped[0].0.rule, "DeprecatedSetuptoolsFormat");
+    }
+}
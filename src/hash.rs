@@ -0,0 +1,106 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+const NIX_BASE32_CHARS: &[u8] = b"0123456789abcdfghijklmnpqrsvwxyz";
+const SHA256_SIZE: usize = 32;
+
+/// Decodes Nix's base32, which unlike RFC 4648 uses its own alphabet and starts at the end.
+fn decode_nix_base32(s: &str) -> Option<Vec<u8>> {
+    let size = s.len() * 5 / 8;
+    let mut bytes = vec![0u8; size];
+
+    for (n, c) in s.bytes().rev().enumerate() {
+        let digit = NIX_BASE32_CHARS.iter().position(|&b| b == c)? as u16;
+        let b = n * 5;
+        let i = b / 8;
+        let j = b % 8;
+        bytes[i] |= (digit << j) as u8;
+
+        let carry = digit >> (8 - j);
+        if i + 1 < size {
+            bytes[i + 1] |= carry as u8;
+        } else if carry != 0 {
+            return None;
+        }
+    }
+
+    Some(bytes)
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Converts the Nix expression of a sha256 hash to an SRI hash string.
+///
+/// Only string literals are converted, `lib.fakeSha256` or `"${...}"` have to be done by hand.
+pub fn sha256_to_sri(expression: &str) -> Result<String, String> {
+    if expression.ends_with("fakeSha256") {
+        return Err(format!(
+            "`{expression}` is left alone, replace it with `lib.fakeHash` by hand"
+        ));
+    }
+
+    let hash = expression
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .ok_or(format!(
+            "`{expression}` isn't a string literal, convert it by hand"
+        ))?;
+
+    if hash.contains("${") {
+        return Err(format!(
+            "`{expression}` is interpolated, convert it by hand"
+        ));
+    }
+
+    if hash.is_empty() || hash.starts_with("sha256-") {
+        return Ok(expression.to_owned());
+    }
+
+    let bytes = match hash.len() {
+        52 => decode_nix_base32(hash),
+        64 => decode_hex(hash),
+        _ => None,
+    }
+    .filter(|bytes| bytes.len() == SHA256_SIZE)
+    .ok_or(format!(
+        "`{expression}` isn't a base16 or base32 sha256 hash"
+    ))?;
+
+    Ok(format!("\"sha256-{}\"", STANDARD.encode(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sha256_to_sri;
+
+    // sha256 of the empty string
+    const SRI: &str = "\"sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=\"";
+
+    #[test]
+    fn base32_to_sri() {
+        assert_eq!(
+            sha256_to_sri("\"0mdqa9w1p6cmli6976v4wi0sw9r4p5prkj7lzfd1877wk11c9c73\""),
+            Ok(SRI.to_string())
+        );
+    }
+
+    #[test]
+    fn base16_to_sri() {
+        assert_eq!(
+            sha256_to_sri("\"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855\""),
+            Ok(SRI.to_string())
+        );
+    }
+
+    #[test]
+    fn unconvertible_to_sri() {
+        assert!(sha256_to_sri("lib.fakeSha256").is_err());
+        assert!(sha256_to_sri("\"${hash}\"").is_err());
+        assert!(sha256_to_sri("\"not a hash\"").is_err());
+        assert_eq!(sha256_to_sri("\"\""), Ok("\"\"".to_string()));
+    }
+}
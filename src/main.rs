@@ -1,32 +1,92 @@
-use std::{env::current_dir, fs::read_to_string, path::PathBuf, process::ExitCode};
+use std::{
+    collections::HashMap,
+    env::current_dir,
+    fs::{read_to_string, write},
+    io::{self, stdout, ErrorKind, StdoutLock, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
 
-use clap::{crate_version, Parser};
-use display::{print_matches, DisplayFormats};
+use clap::{crate_version, Parser, Subcommand};
+use config::Config;
+use display::{
+    json_schema, write_diff, write_matches, write_output, DisplayFormats, Output, RunInfo,
+};
 use find::find_nix_files;
 use find_lints::find_lints;
+use fix::{fix_matches, FileFix};
 use indicatif::{ParallelProgressIterator, ProgressBar};
-use queries::{add_default_queries, add_unfinished_queries};
-use query::{AMatch, AQuery};
+use queries::QUERIES;
+use query::{run_fails, AMatch, AQuery, Severity};
 use rayon::prelude::*;
+use rules::{write_explanation, write_rules, RulesFormat};
+use select::Selection;
 
+mod config;
 mod display;
 mod find;
 mod find_lints;
+mod fix;
+mod hash;
 mod queries;
 mod query;
+mod rules;
+mod select;
+mod suppression;
 
 fn main() -> ExitCode {
-    let args = Opt::parse();
-    let mut match_vec: Vec<AMatch> = Vec::new();
+    let opt = Opt::parse();
 
-    let mut queries: Vec<AQuery> = Vec::new();
+    match opt.command {
+        None => check(opt.check),
+        Some(Command::Check(args)) => check(args),
+        Some(Command::ListRules { format }) => {
+            let rules: Vec<&AQuery> = QUERIES.values().collect();
+            print(|out| write_rules(out, &format, &rules))
+        }
+        Some(Command::Schema) => print(|out| write!(out, "{}", json_schema())),
+        Some(Command::Explain { id }) => match QUERIES.get(&id) {
+            Some(q) => print(|out| write_explanation(out, q)),
+            None => {
+                eprintln!("unknown lint '{id}', see `nixpkgs-lint list-rules`");
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
 
-    add_default_queries(&mut queries);
+fn check(args: CheckOpt) -> ExitCode {
+    let start = Instant::now();
+    let terminal = Terminal::default();
+    let mut match_vec: Vec<AMatch> = Vec::new();
+    // of the matches which were streamed and dropped
+    let mut severities: Vec<Severity> = Vec::new();
+    let mut run = RunInfo {
+        base_url: args.base_url.clone(),
+        ..Default::default()
+    };
+    let mut format = args.format;
+    if format.is_none() && args.running_in_nixpkgs_ci {
+        format = Some(DisplayFormats::Github);
+    }
 
-    if args.include_unfinished_lints {
-        add_unfinished_queries(&mut queries);
+    let selection = match Selection::new(&args.select, &args.ignore) {
+        Ok(selection) => selection,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
     };
 
+    // every path uses the lints of its own config, fixed files are linted again with them
+    let mut query_sets: Vec<Vec<AQuery>> = Vec::new();
+    let mut queries_of_file: HashMap<String, usize> = HashMap::new();
+
     for mut path in args.file {
         if let Ok(false) = &path.try_exists() {
             eprintln!("path '{}' does not exist", path.to_string_lossy());
@@ -35,54 +95,275 @@ fn main() -> ExitCode {
         if path.to_string_lossy() == "." {
             path = current_dir().unwrap();
         }
-        let entries = find_nix_files(&path);
+
+        let config = match Config::discover(&path) {
+            Ok(config) => config.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("invalid config: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+        if format.is_none() {
+            format = config.format.clone();
+        }
+        let queries = config.queries(args.include_unfinished_lints, &selection);
+
+        let root = path.canonicalize().unwrap();
+        let mut entries = find_nix_files(&path);
+        entries.retain(|entry| {
+            let absolute = match Path::new(entry).strip_prefix(&path) {
+                Ok(relative) if !relative.as_os_str().is_empty() => root.join(relative),
+                _ => root.clone(),
+            };
+            !config.is_excluded(&absolute)
+        });
+        run.files.extend(entries.iter().cloned());
+        if args.fix {
+            queries_of_file.extend(entries.iter().map(|e| (e.to_owned(), query_sets.len())));
+        }
+
         let length: u64 = entries.len().try_into().unwrap();
         let mut pb = ProgressBar::hidden();
         if length > 1000 {
             pb = ProgressBar::new(length);
         }
 
-        match_vec.par_extend(entries.into_par_iter().progress_with(pb).flat_map(|entry| {
+        // ndjson is printed file by file, the matches are only kept if something else needs them
+        let stream = matches!(format, Some(DisplayFormats::Ndjson)) && !args.fix && !args.diff;
+        let lints = entries.into_par_iter().progress_with(pb).map(|entry| {
             //println!("{:?}", entry);
             let file_contents = read_to_string(&entry).unwrap();
 
-            find_lints(
-                &entry,
-                file_contents.trim(),
-                &queries,
-                &args.node_debug,
-            )
-        }));
+            let matches = find_lints(&entry, file_contents.trim(), &queries, &args.node_debug);
+            if stream {
+                terminal.write(|out| write_matches(out, &DisplayFormats::Ndjson, &run, &matches));
+            }
+            matches
+        });
+        if stream && args.output.is_empty() {
+            severities.par_extend(lints.flat_map_iter(|ms| ms.into_iter().map(|m| m.severity)));
+        } else {
+            match_vec.par_extend(lints.flatten());
+        }
+
+        for q in &queries {
+            if !run.rules.iter().any(|r| r.id == q.id) {
+                run.rules.push(q.clone());
+            }
+        }
+        query_sets.push(queries);
     }
 
-    if !match_vec.is_empty() {
-        print_matches(&args.format, &match_vec);
+    if args.diff {
+        let fixes = fix_matches(match_vec);
+        fixes.iter().for_each(print_skipped);
+        terminal.write(|out| write_diff(out, &fixes));
+        if let Err(e) = terminal.finish() {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+
+        // like `diff`, fail only if there's a difference
+        if fixes.iter().any(FileFix::is_changed) {
+            return ExitCode::FAILURE;
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let mut unwritable = false;
+    if args.fix {
+        let fixes = fix_matches(match_vec);
+        match_vec = Vec::new();
+
+        for f in fixes {
+            print_skipped(&f);
+
+            if f.is_changed() {
+                if let Err(e) = write(&f.file, &f.fixed) {
+                    eprintln!("couldn't write {}: {e}", f.file);
+                    unwritable = true;
+                    match_vec.extend(f.applied);
+                    match_vec.extend(f.skipped.into_iter().map(|(m, _)| m));
+                    continue;
+                }
+                eprintln!("fixed {} issue(s) in {}", f.applied.len(), f.file);
+                // the remaining matches' positions are stale now
+                let queries = &query_sets[queries_of_file[&f.file]];
+                match_vec.extend(find_lints(&f.file, f.fixed.trim(), queries, &false));
+            } else {
+                match_vec.extend(f.skipped.into_iter().map(|(m, _)| m));
+            }
+        }
+    }
+
+    run.duration = start.elapsed();
+    let format = format.unwrap_or(DisplayFormats::Ariadne);
+    let streamed = matches!(format, DisplayFormats::Ndjson) && !args.fix;
+    if !streamed && (!match_vec.is_empty() || format.is_document()) {
+        terminal.write(|out| write_matches(out, &format, &run, &match_vec));
+    }
+    if let Err(e) = terminal.finish() {
+        eprintln!("{e}");
+        return ExitCode::FAILURE;
+    }
+    for output in &args.output {
+        if let Err(e) = write_output(output, &run, &match_vec) {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    severities.extend(match_vec.iter().map(|m| m.severity));
+    // a warning would fail the run before `--max-warnings` is reached
+    let fail_on = args.fail_on.unwrap_or(match args.max_warnings {
+        Some(_) => Severity::Error,
+        None => Severity::Hint,
+    });
+    if unwritable || run_fails(&severities, fail_on, args.max_warnings) {
         return ExitCode::FAILURE;
     }
 
     ExitCode::SUCCESS
 }
 
+/// Writes to stdout until its reader is gone, e.g. `nixpkgs-lint --format ndjson | head`.
+#[derive(Default)]
+struct Terminal {
+    closed: AtomicBool,
+    error: Mutex<Option<io::Error>>,
+}
+
+impl Terminal {
+    fn write(&self, f: impl FnOnce(&mut StdoutLock) -> io::Result<()>) {
+        if self.closed.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut out = stdout().lock();
+        if let Err(e) = f(&mut out) {
+            self.closed.store(true, Ordering::Relaxed);
+            if e.kind() != ErrorKind::BrokenPipe {
+                self.error.lock().unwrap().get_or_insert(e);
+            }
+        }
+    }
+
+    /// The first error other than a closed pipe
+    fn finish(&self) -> Result<(), String> {
+        match self.error.lock().unwrap().take() {
+            Some(e) => Err(format!("couldn't write to stdout: {e}")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes the output of a subcommand to stdout.
+fn print(f: impl FnOnce(&mut StdoutLock) -> io::Result<()>) -> ExitCode {
+    let terminal = Terminal::default();
+    terminal.write(f);
+    match terminal.finish() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn print_skipped(f: &FileFix) {
+    for (m, reason) in &f.skipped {
+        eprintln!(
+            "{}:{}:{}: couldn't fix [{}] {}: {reason}",
+            m.file, m.line, m.column, m.rule, m.message
+        );
+    }
+}
+
 #[derive(Parser, Debug)]
-#[clap(version = crate_version!())]
+#[clap(version = crate_version!(), args_conflicts_with_subcommands = true)]
 struct Opt {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// `nixpkgs-lint <FILES>` is short for `nixpkgs-lint check <FILES>`
+    #[clap(flatten)]
+    check: CheckOpt,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Lint files or directories, the default
+    Check(CheckOpt),
+    /// List every lint with its id, category, maturity and fix
+    ListRules {
+        #[clap(value_enum, long, default_value_t = RulesFormat::Table)]
+        format: RulesFormat,
+    },
+    /// Explain why a lint exists, with examples and its tree-sitter query
+    Explain {
+        /// the id of the lint, e.g. BuildTimeToolInBuildInputs
+        id: String,
+    },
+    /// Print the JSON Schema of `--format json`
+    Schema,
+}
+
+#[derive(clap::Args, Debug)]
+struct CheckOpt {
     /// Files or directories
     #[clap(value_name = "FILES/DIRECTORIES")]
     file: Vec<PathBuf>,
 
-    /// Output format
-    #[clap(value_enum, long, default_value_t = DisplayFormats::Ariadne)]
-    format: DisplayFormats,
+    /// Output format [default: the config's format or ariadne, github in nixpkgs ci]
+    #[clap(value_enum, long)]
+    format: Option<DisplayFormats>,
+
+    /// also write a report in this format to a file, e.g. `--output sarif=out.sarif`,
+    /// can be repeated
+    #[clap(long, value_name = "FORMAT=PATH")]
+    output: Vec<Output>,
+
+    /// links to the files in reports start with this,
+    /// e.g. https://github.com/NixOS/nixpkgs/blob/master
+    #[clap(long, value_name = "URL")]
+    base_url: Option<String>,
+
+    /// fail if there's a match of this severity or a more severe one
+    /// [default: hint, error with `--max-warnings`]
+    #[clap(value_enum, long)]
+    fail_on: Option<Severity>,
+
+    /// fail if there are more warnings than this, even if `--fail-on` ignores them
+    #[clap(long, value_name = "N")]
+    max_warnings: Option<usize>,
+
+    /// apply automatic fixes to the files in place
+    #[clap(long, conflicts_with = "node_debug")]
+    fix: bool,
+
+    /// print the automatic fixes as a unified diff instead of applying them
+    #[clap(long, conflicts_with_all = ["fix", "node_debug"])]
+    diff: bool,
+
+    /// only run these lints, by id, glob of ids (`Deprecated*`), category
+    /// (general, stdenv, meta, fetchers, python) or maturity (stable, experimental, nursery,
+    /// unfinished), lints disabled in the config only run when selected by id
+    #[clap(long, value_delimiter = ',', value_name = "SELECTOR")]
+    select: Vec<String>,
+
+    /// don't run these lints, takes the same selectors as `--select`
+    #[clap(long, value_delimiter = ',', value_name = "SELECTOR")]
+    ignore: Vec<String>,
 
     /// debug nodes
     #[clap(long = "node-debug")]
     node_debug: bool,
 
-    /// use lints which haven't been fixed in nixpkgs yet
+    /// also run the experimental and nursery lints, which haven't been fixed in nixpkgs yet
     #[clap(long = "include-unfinished-lints")]
     include_unfinished_lints: bool,
 
-    /// enable if running in nixpkgs ci
+    /// enable if running in nixpkgs ci, reports matches as GitHub Actions annotations
+    /// unless `--format` is passed
     #[clap(
         conflicts_with = "include_unfinished_lints",
         long = "running-in-nixpkgs-ci"
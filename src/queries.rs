@@ -1,301 +1,678 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use lazy_static::lazy_static;
 
-use crate::query::{AQuery, QueryType, TypeOfFix};
+use crate::query::{AQuery, Category, Example, Maturity, QueryType, Rewrite, Severity, TypeOfFix};
 
 lazy_static! {
-    pub static ref QUERIES: HashMap<&'static str, AQuery> = HashMap::from([
-        (
-            "BuildTimeToolInBuildInputs",
-            (AQuery {
-                name: "build time tool in buildInputs".to_string(),
-                solution: "move this from buildInputs to nativeBuildInputs".to_string(),
-                context: String::new(),
-                what: "cmake|makeWrapper|pkg-config|intltool|autoreconfHook".to_string(),
-                in_what: "buildInputs".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Move,
-            }),
-        ),
-        (
-            "FlagsNotList",
-            (AQuery {
-                name: "*Flags not a list".to_string(),
-                solution: "convert to a list".to_string(),
-                context: String::new(),
-                what: String::new(),
-                in_what: "Flags".to_string(),
-                type_of_query: QueryType::BindingAStringInsteadOfList,
-                type_of_fix: TypeOfFix::ConvertToList,
-            }),
-        ),
-        (
-            "ArgsToOptionalIsList",
-            (AQuery {
-                name: "Arg to lib.optional is a list".to_string(),
-                solution: "change lib.optional to lib.optionals".to_string(),
-                context: String::new(),
-                what: String::new(),
-                in_what: String::new(),
-                type_of_query: QueryType::ArgToOptionalAList,
-                type_of_fix: TypeOfFix::Change,
-            }),
-        ),
-        (
-            "UnnormalizedPythonPname",
-            (AQuery {
-                name: "unnormalized python pname".to_string(),
-                solution: "normalize this according to PEP503, for example, lowercase and use `-` instead of `.` and `_`".to_string(),
-                context: "buildPythonPackage".to_string(),
-                what: "[A-Z._]".to_string(),
-                in_what: "pname".to_string(),
-                type_of_query: QueryType::AttrValueInContext,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-    ]);
+    pub static ref QUERIES: BTreeMap<String, AQuery> = [
+        AQuery {
+            id: "BuildTimeToolInBuildInputs".to_string(),
+            category: Category::Stdenv,
+            maturity: Maturity::Stable,
+            since: "0.3.0".to_string(),
+            name: "build time tool in buildInputs".to_string(),
+            solution: "move this from buildInputs to nativeBuildInputs".to_string(),
+            explanation: "Tools which run during the build, like cmake or pkg-config, belong in nativeBuildInputs so that they are built for the build platform. In buildInputs they break cross compilation.".to_string(),
+            example: Example {
+                bad: "stdenv.mkDerivation { buildInputs = [ cmake zlib ]; }".to_string(),
+                good: "stdenv.mkDerivation { nativeBuildInputs = [ cmake ]; buildInputs = [ zlib ]; }".to_string(),
+            },
+            context: String::new(),
+            what: "cmake|makeWrapper|pkg-config|intltool|autoreconfHook".to_string(),
+            in_what: "buildInputs".to_string(),
+            destination: "nativeBuildInputs".to_string(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Move,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "FlagsNotList".to_string(),
+            category: Category::Stdenv,
+            maturity: Maturity::Stable,
+            since: "0.3.0".to_string(),
+            name: "*Flags not a list".to_string(),
+            solution: "convert to a list".to_string(),
+            explanation: "Flags in a string are split on whitespace, so a flag can't contain a space. A list passes every flag as is and is required with `__structuredAttrs`.".to_string(),
+            example: Example {
+                bad: r#"stdenv.mkDerivation { configureFlags = "--enable-foo --disable-bar"; }"#.to_string(),
+                good: r#"stdenv.mkDerivation { configureFlags = [ "--enable-foo" "--disable-bar" ]; }"#.to_string(),
+            },
+            context: String::new(),
+            what: String::new(),
+            in_what: "Flags".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::BindingAStringInsteadOfList,
+            type_of_fix: TypeOfFix::ConvertToList,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "ArgsToOptionalIsList".to_string(),
+            category: Category::General,
+            maturity: Maturity::Stable,
+            since: "0.3.0".to_string(),
+            name: "Arg to lib.optional is a list".to_string(),
+            solution: "change lib.optional to lib.optionals".to_string(),
+            explanation: "`lib.optional` wraps its argument in a list, a list argument ends up nested in another list. `lib.optionals` returns the list itself.".to_string(),
+            example: Example {
+                bad: "stdenv.mkDerivation { buildInputs = lib.optional stdenv.isLinux [ systemd ]; }".to_string(),
+                good: "stdenv.mkDerivation { buildInputs = lib.optionals stdenv.isLinux [ systemd ]; }".to_string(),
+            },
+            context: String::new(),
+            what: String::new(),
+            in_what: String::new(),
+            destination: String::new(),
+            type_of_query: QueryType::ArgToOptionalAList,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "UnnormalizedPythonPname".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Stable,
+            since: "0.3.0".to_string(),
+            name: "unnormalized python pname".to_string(),
+            solution: "normalize this according to PEP503, for example, lowercase and use `-` instead of `.` and `_`".to_string(),
+            explanation: "Python package names are normalized according to PEP 503: lowercase, with runs of `-`, `_` and `.` replaced by a single `-`. Tools such as the update scripts rely on it to find the package on PyPI.".to_string(),
+            example: Example {
+                bad: r#"buildPythonPackage { pname = "Foo_Bar"; }"#.to_string(),
+                good: r#"buildPythonPackage { pname = "foo-bar"; }"#.to_string(),
+            },
+            context: "buildPythonPackage".to_string(),
+            what: "[A-Z._]".to_string(),
+            in_what: "pname".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::AttrValueInContext,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "UnusedSuppression".to_string(),
+            category: Category::General,
+            maturity: Maturity::Stable,
+            since: "0.3.0".to_string(),
+            name: "unused suppression".to_string(),
+            solution: "remove this from the nixpkgs-lint-disable comment".to_string(),
+            explanation: "A suppression which silences nothing is stale, the lint has been fixed or doesn't apply anymore. Left alone it would hide the lint when the code changes again.".to_string(),
+            example: Example {
+                bad: r#"{
+  # nixpkgs-lint-disable-next-line FlagsNotList
+  configureFlags = [ "--foo" ];
+}"#.to_string(),
+                good: r#"{
+  configureFlags = [ "--foo" ];
+}"#.to_string(),
+            },
+            context: String::new(),
+            what: String::new(),
+            in_what: String::new(),
+            destination: String::new(),
+            type_of_query: QueryType::UnusedSuppression,
+            type_of_fix: TypeOfFix::Remove,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "RedundantPackageFromStdenv".to_string(),
+            category: Category::Stdenv,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "redundant package from stdenv in nativeBuildInputs".to_string(),
+            solution: "remove this from nativeBuildInputs".to_string(),
+            explanation: "stdenv already provides coreutils, gnumake, bash and the other tools of the standard environment, listing them again has no effect.".to_string(),
+            example: Example {
+                bad: "stdenv.mkDerivation { nativeBuildInputs = [ gnumake pkg-config ]; }".to_string(),
+                good: "stdenv.mkDerivation { nativeBuildInputs = [ pkg-config ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"coreutils|findutils|diffutils|gnugrep|gawk|gnutar|gzip|bzip2\.bin|gnumake|bash|patch|xz\.bin|file".to_string(),
+            in_what: "nativeBuildInputs".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Remove,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "StartsWithDefiniteOrIndefiniteArticleInDescription".to_string(),
+            category: Category::Meta,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "starts with definite or indefinite article in description".to_string(),
+            solution: "remove a definite/indefinite article from meta.description".to_string(),
+            explanation: "meta.description is shown after the name of the package, an article at its start is redundant.".to_string(),
+            example: Example {
+                bad: r#"stdenv.mkDerivation { meta = { description = "A linter for Nixpkgs"; }; }"#.to_string(),
+                good: r#"stdenv.mkDerivation { meta = { description = "Linter for Nixpkgs"; }; }"#.to_string(),
+            },
+            context: String::new(),
+            what: r"^(A|The) ".to_string(),
+            in_what: "description".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::String,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Info,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "NoCapitalizationInDescription".to_string(),
+            category: Category::Meta,
+            maturity: Maturity::Nursery,
+            since: "0.3.0".to_string(),
+            name: "no capitalization in description".to_string(),
+            solution: "be capitalized".to_string(),
+            explanation: "meta.description should start with a capital letter. Names which are lowercase by convention are false positives for now.".to_string(),
+            example: Example {
+                bad: r#"stdenv.mkDerivation { meta = { description = "linter for Nixpkgs"; }; }"#.to_string(),
+                good: r#"stdenv.mkDerivation { meta = { description = "Linter for Nixpkgs"; }; }"#.to_string(),
+            },
+            context: String::new(),
+            what: r"^[a-z]".to_string(),
+            in_what: "description".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::String,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Info,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "EndsWithPeriodInDescription".to_string(),
+            category: Category::Meta,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "ends with period in description".to_string(),
+            solution: "remove a period from meta.description".to_string(),
+            explanation: "meta.description is a short summary rather than a sentence, it shouldn't end with a period.".to_string(),
+            example: Example {
+                bad: r#"stdenv.mkDerivation { meta = { description = "Linter for Nixpkgs."; }; }"#.to_string(),
+                good: r#"stdenv.mkDerivation { meta = { description = "Linter for Nixpkgs"; }; }"#.to_string(),
+            },
+            context: String::new(),
+            what: r"\\.$".to_string(),
+            in_what: "description".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::String,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Info,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "RecKeywordWithMkDerivation".to_string(),
+            category: Category::Stdenv,
+            maturity: Maturity::Nursery,
+            since: "0.3.0".to_string(),
+            name: "rec keyword with mkDerivation".to_string(),
+            solution: "consider to use finalAttrs pattern".to_string(),
+            explanation: "Attributes referenced through `rec` don't see changes made with overrideAttrs. Passing a function of `finalAttrs` to mkDerivation refers to the final attributes instead.".to_string(),
+            example: Example {
+                bad: r#"stdenv.mkDerivation rec { pname = "foo"; version = "1.0"; }"#.to_string(),
+                good: r#"stdenv.mkDerivation (finalAttrs: { pname = "foo"; version = "1.0"; })"#.to_string(),
+            },
+            context: String::new(),
+            what: String::new(),
+            in_what: "mkDerivation".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::RecursiveAttrSet,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Hint,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "RefsTagsWithRev".to_string(),
+            category: Category::Fetchers,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "refs/tags with rev".to_string(),
+            solution: "replace rev with tag and remove the prefix, refs/tags/".to_string(),
+            explanation: "fetchFromGitHub takes tags in `tag`, which is clearer than a `refs/tags/` prefix in `rev` and tells update scripts that the source is a tag.".to_string(),
+            example: Example {
+                bad: r#"fetchFromGitHub { owner = "owner"; repo = "repo"; rev = "refs/tags/v1.0"; }"#.to_string(),
+                good: r#"fetchFromGitHub { owner = "owner"; repo = "repo"; tag = "v1.0"; }"#.to_string(),
+            },
+            context: "fetchFromGitHub".to_string(),
+            what: "^refs/tags/".to_string(),
+            in_what: "rev".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::AttrValueInContext,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![
+                Rewrite {
+                    capture: "a".to_string(),
+                    template: "tag".to_string(),
+                },
+                Rewrite {
+                    capture: "q".to_string(),
+                    template: "{q#refs/tags/}".to_string(),
+                },
+            ],
+        },
+        AQuery {
+            id: "SRIHash".to_string(),
+            category: Category::Fetchers,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "SRI hash".to_string(),
+            solution: "replace this with hash and convert the value to an SRI hash".to_string(),
+            explanation: "SRI hashes name their algorithm and are what the fetchers and `nix hash` print, `sha256` in base16 or base32 is the legacy format.".to_string(),
+            example: Example {
+                bad: r#"fetchFromGitHub { owner = "owner"; repo = "repo"; tag = "v1.0"; sha256 = "0mdqa9w1p6cmli6976v4wi0sw9r4p5prkj7lzfd1877wk11c9c73"; }"#.to_string(),
+                good: r#"fetchFromGitHub { owner = "owner"; repo = "repo"; tag = "v1.0"; hash = "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="; }"#.to_string(),
+            },
+            context: String::new(),
+            what: "sha256".to_string(),
+            in_what: "fetchFromGitHub|fetchPypi".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::AttrNameInFunction,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![
+                Rewrite {
+                    capture: "q".to_string(),
+                    template: "hash".to_string(),
+                },
+                Rewrite {
+                    capture: "v".to_string(),
+                    template: "{v|sri}".to_string(),
+                },
+            ],
+        },
+        AQuery {
+            id: "BindingWithExpression".to_string(),
+            category: Category::Meta,
+            maturity: Maturity::Nursery,
+            since: "0.3.0".to_string(),
+            name: "binding with expression".to_string(),
+            solution: "remove with expression".to_string(),
+            explanation: "`with lib;` over meta hides where the names come from and can shadow other bindings, neither readers nor static analysis can resolve them.".to_string(),
+            example: Example {
+                bad: "stdenv.mkDerivation { meta = with lib; { license = licenses.mit; }; }".to_string(),
+                good: "stdenv.mkDerivation { meta = { license = lib.licenses.mit; }; }".to_string(),
+            },
+            context: String::new(),
+            what: "lib".to_string(),
+            in_what: "meta".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::BindingWithExpression,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "DeprecatedFormatAttributeUsage".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "deprecated format attribute usage".to_string(),
+            solution: "set `pyproject = true` instead".to_string(),
+            explanation: r#"`format = "pyproject";` of buildPythonPackage is deprecated in favour of `pyproject = true;`."#.to_string(),
+            example: Example {
+                bad: r#"buildPythonPackage { format = "pyproject"; }"#.to_string(),
+                good: "buildPythonPackage { pyproject = true; }".to_string(),
+            },
+            context: String::new(),
+            what: r"pyproject".to_string(),
+            in_what: "format".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::String,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![
+                Rewrite {
+                    capture: "q".to_string(),
+                    template: "pyproject = true;".to_string(),
+                },
+            ],
+        },
+        AQuery {
+            id: "DeprecatedSetuptoolsFormat".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "deprecated format attribute usage".to_string(),
+            solution: "set `pyproject = true` and add setuptools to `build-system`".to_string(),
+            explanation: r#"`format = "setuptools";` is deprecated in favour of `pyproject = true;` with the build backend in `build-system`. There's no automatic fix, `pyproject = true;` alone drops the build backend."#.to_string(),
+            example: Example {
+                bad: r#"buildPythonPackage { format = "setuptools"; }"#.to_string(),
+                good: "buildPythonPackage { pyproject = true; build-system = [ setuptools ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"setuptools".to_string(),
+            in_what: "format".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::String,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "DeprecatedFormatOtherAttributeUsage".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "deprecated format attribute usage".to_string(),
+            solution: "set `pyproject = false` instead".to_string(),
+            explanation: r#"`format = "other";` is deprecated, `pyproject = false;` builds the package without a Python build backend."#.to_string(),
+            example: Example {
+                bad: r#"buildPythonPackage { format = "other"; }"#.to_string(),
+                good: "buildPythonPackage { pyproject = false; }".to_string(),
+            },
+            context: String::new(),
+            what: r"other".to_string(),
+            in_what: "format".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::String,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![
+                Rewrite {
+                    capture: "q".to_string(),
+                    template: "pyproject = false;".to_string(),
+                },
+            ],
+        },
+        AQuery {
+            id: "UnnecessaryWheel".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "unnecessary wheel in build-system".to_string(),
+            solution: "remove this from build-system".to_string(),
+            explanation: "Build backends which need wheel depend on it themselves, listing it in build-system is redundant.".to_string(),
+            example: Example {
+                bad: "buildPythonPackage { build-system = [ setuptools wheel ]; }".to_string(),
+                good: "buildPythonPackage { build-system = [ setuptools ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"wheel".to_string(),
+            in_what: "build-system".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Remove,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "PythonPackageInNativeBuildInputs".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "python package in nativeBuildInputs".to_string(),
+            solution: "move this from nativeBuildInputs to build-system".to_string(),
+            explanation: "Python build backends belong in build-system, where buildPythonPackage adds them to the right environment.".to_string(),
+            example: Example {
+                bad: "buildPythonPackage { nativeBuildInputs = [ setuptools ]; }".to_string(),
+                good: "buildPythonPackage { build-system = [ setuptools ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"setuptools|setuptools-scm|hatchling|flit-core|poetry-core|pdm-backend|wheel|maturinBuildHook|".to_string(),
+            in_what: "nativeBuildInputs".to_string(),
+            destination: "build-system".to_string(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Move,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "RedundantPackageInNativeBuildInputs".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Nursery,
+            since: "0.3.0".to_string(),
+            name: "redundant package in nativeBuildInputs".to_string(),
+            solution: "remove this from nativeBuildInputs".to_string(),
+            explanation: "buildPythonPackage adds pythonRelaxDepsHook by itself, it's enough to set pythonRelaxDeps.".to_string(),
+            example: Example {
+                bad: r#"buildPythonPackage { nativeBuildInputs = [ pythonRelaxDepsHook ]; pythonRelaxDeps = [ "numpy" ]; }"#.to_string(),
+                good: r#"buildPythonPackage { pythonRelaxDeps = [ "numpy" ]; }"#.to_string(),
+            },
+            context: String::new(),
+            what: r"pythonRelaxDepsHook".to_string(),
+            in_what: "nativeBuildInputs".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Remove,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "VersionedPackageInDependencies".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Nursery,
+            since: "0.3.0".to_string(),
+            name: "versioned package in dependencies".to_string(),
+            solution: "change `package_X_Y` to `package`".to_string(),
+            explanation: "Versioned attributes like `numpy_2` are temporary, packages should depend on the default version so they keep working when the versioned attribute is removed.".to_string(),
+            example: Example {
+                bad: "buildPythonPackage { dependencies = [ numpy_2 ]; }".to_string(),
+                good: "buildPythonPackage { dependencies = [ numpy ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"[a-z0-9-]+_[0-9]+".to_string(),
+            in_what: "dependencies".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "RemovePytestCov".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "pytest-cov in nativeCheckInputs".to_string(),
+            solution: "remove this from nativeCheckInputs or change to pytest-cov-stub".to_string(),
+            explanation: "Coverage reports are thrown away in the sandbox and pytest-cov slows the tests down. pytest-cov-stub accepts the `--cov` flags without doing anything.".to_string(),
+            example: Example {
+                bad: "buildPythonPackage { nativeCheckInputs = [ pytestCheckHook pytest-cov ]; }".to_string(),
+                good: "buildPythonPackage { nativeCheckInputs = [ pytestCheckHook pytest-cov-stub ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"pytest-cov".to_string(),
+            in_what: "nativeCheckInputs".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Remove,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "PytestBenchmarkInNativeCheckInputs".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "pytest-benchmark in nativeCheckInputs".to_string(),
+            solution: "remove this from nativeCheckInputs or pass `--benchmark-disable` to pytestFlagsArray".to_string(),
+            explanation: "Benchmarks take a long time and their timings are meaningless in the sandbox.".to_string(),
+            example: Example {
+                bad: "buildPythonPackage { nativeCheckInputs = [ pytestCheckHook pytest-benchmark ]; }".to_string(),
+                good: "buildPythonPackage { nativeCheckInputs = [ pytestCheckHook ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"pytest-benchmark".to_string(),
+            in_what: "nativeCheckInputs".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Remove,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "NonFunctionalTestingToolInNativeCheckInputs".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "non functional testing tool in nativeCheckInputs".to_string(),
+            solution: "remove this from nativeCheckInputs".to_string(),
+            explanation: "Linters and formatters like flake8 or black don't test the package, running them only breaks the build on style changes.".to_string(),
+            example: Example {
+                bad: "buildPythonPackage { nativeCheckInputs = [ pytestCheckHook flake8 ]; }".to_string(),
+                good: "buildPythonPackage { nativeCheckInputs = [ pytestCheckHook ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"pytest-runner|flake8|black|isort|coverage|ruff".to_string(),
+            in_what: "nativeCheckInputs".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Remove,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "DeprecatedTestingToolInNativeCheckInputs".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "deprecated testing tool in nativeCheckInputs".to_string(),
+            solution: "remove this from nativeCheckInputs".to_string(),
+            explanation: "nose is unmaintained and broken on recent Python versions, the tests should run with pytest.".to_string(),
+            example: Example {
+                bad: "buildPythonPackage { nativeCheckInputs = [ nose ]; }".to_string(),
+                good: "buildPythonPackage { nativeCheckInputs = [ pytestCheckHook ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"nose".to_string(),
+            in_what: "nativeCheckInputs".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Remove,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+        AQuery {
+            id: "BarePytestInNativeCheckInputs".to_string(),
+            category: Category::Python,
+            maturity: Maturity::Nursery,
+            since: "0.3.0".to_string(),
+            name: "bare pytest in nativeCheckInputs".to_string(),
+            solution: "change pytest to pytestCheckHook".to_string(),
+            explanation: "pytestCheckHook runs pytest in the check phase and understands disabledTests and the other options of the hook, bare pytest needs a custom checkPhase.".to_string(),
+            example: Example {
+                bad: "buildPythonPackage { nativeCheckInputs = [ pytest ]; }".to_string(),
+                good: "buildPythonPackage { nativeCheckInputs = [ pytestCheckHook ]; }".to_string(),
+            },
+            context: String::new(),
+            what: r"pytest".to_string(),
+            in_what: "nativeCheckInputs".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::List,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![],
+        },
+    ]
+    .into_iter()
+    .map(|q| (q.id.clone(), q))
+    .collect();
+}
 
-    pub static ref UNFINISHED_QUERIES: HashMap<&'static str, AQuery> = HashMap::from([
-        (
-            "RedundantPackageFromStdenv",
-            (AQuery {
-                name: "redundant package from stdenv in nativeBuildInputs".to_string(),
-                solution: "remove this from nativeBuildInputs".to_string(),
-                context: String::new(),
-                what: r"coreutils|findutils|diffutils|gnugrep|gawk|gnutar|gzip|bzip2\.bin|gnumake|bash|patch|xz\.bin|file".to_string(),
-                in_what: "nativeBuildInputs".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Remove,
-            })
-        ),
-        (
-            "StartsWithDefiniteOrIndefiniteArticleInDescription",
-            (AQuery {
-                name: "starts with definite or indefinite article in description".to_string(),
-                solution: "remove a definite/indefinite article from meta.description".to_string(),
-                context: String::new(),
-                what: r"^(A|The) ".to_string(),
-                in_what: "description".to_string(),
-                type_of_query: QueryType::String,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "NoCapitalizationInDescription",
-            (AQuery {
-                name: "no capitalization in description".to_string(),
-                solution: "be capitalized".to_string(),
-                context: String::new(),
-                what: r"^[a-z]".to_string(),
-                in_what: "description".to_string(),
-                type_of_query: QueryType::String,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "EndsWithPeriodInDescription",
-            (AQuery {
-                name: "ends with period in description".to_string(),
-                solution: "remove a period from meta.description".to_string(),
-                context: String::new(),
-                what: r"\\.$".to_string(),
-                in_what: "description".to_string(),
-                type_of_query: QueryType::String,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "RecKeywordWithMkDerivation",
-            (AQuery {
-                name: "rec keyword with mkDerivation".to_string(),
-                solution: "consider to use finalAttrs pattern".to_string(),
-                context: String::new(),
-                what: String::new(),
-                in_what: "mkDerivation".to_string(),
-                type_of_query: QueryType::RecursiveAttrSet,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "RefsTagsWithRev",
-            (AQuery {
-                name: "refs/tags with rev".to_string(),
-                solution: "replace rev with tag and remove the prefix, refs/tags/".to_string(),
-                context: "fetchFromGitHub".to_string(),
-                what: "^refs/tags/".to_string(),
-                in_what: "rev".to_string(),
-                type_of_query: QueryType::AttrValueInContext,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "SRIHash",
-            (AQuery {
-                name: "SRI hash".to_string(),
-                solution: "replace this with hash".to_string(),
-                context: String::new(),
-                what: "sha256".to_string(),
-                in_what: "fetchFromGitHub|fetchPypi".to_string(),
-                type_of_query: QueryType::AttrNameInFunction,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "BindingWithExpression",
-            (AQuery {
-                name: "binding with expression".to_string(),
-                solution: "remove with expression".to_string(),
-                context: String::new(),
-                what: "lib".to_string(),
-                in_what: "meta".to_string(),
-                type_of_query: QueryType::BindingWithExpression,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "DeprecatedFormatAttributeUsage",
-            (AQuery {
-                name: "deprecated format attribute usage".to_string(),
-                solution: "set `pyproject = true` instead".to_string(),
-                context: String::new(),
-                what: r"setuptools|pyproject".to_string(),
-                in_what: "format".to_string(),
-                type_of_query: QueryType::String,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "DeprecatedFormatOtherAttributeUsage",
-            (AQuery {
-                name: "deprecated format attribute usage".to_string(),
-                solution: "set `pyproject = false` instead".to_string(),
-                context: String::new(),
-                what: r"other".to_string(),
-                in_what: "format".to_string(),
-                type_of_query: QueryType::String,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "UnnecessaryWheel",
-            (AQuery {
-                name: "unnecessary wheel in build-system".to_string(),
-                solution: "remove this from build-system".to_string(),
-                context: String::new(),
-                what: r"wheel".to_string(),
-                in_what: "build-system".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Remove,
-            })
-        ),
-        (
-            "PythonPackageInNativeBuildInputs",
-            (AQuery {
-                name: "python package in nativeBuildInputs".to_string(),
-                solution: "move this from nativeBuildInputs to build-system".to_string(),
-                context: String::new(),
-                what: r"setuptools|setuptools-scm|hatchling|flit-core|poetry-core|pdm-backend|wheel|maturinBuildHook|".to_string(),
-                in_what: "nativeBuildInputs".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Move,
-            })
-        ),
-        (
-            "RedundantPackageInNativeBuildInputs",
-            (AQuery {
-                name: "redundant package in nativeBuildInputs".to_string(),
-                solution: "remove this from nativeBuildInputs".to_string(),
-                context: String::new(),
-                what: r"pythonRelaxDepsHook".to_string(),
-                in_what: "nativeBuildInputs".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Move,
-            })
-        ),
-        (
-            "VersionedPackageInDependencies",
-            (AQuery {
-                name: "versioned package in dependencies".to_string(),
-                solution: "change `package_X_Y` to `package`".to_string(),
-                context: String::new(),
-                what: r"[a-z0-9-]+_[0-9]+".to_string(),
-                in_what: "dependencies".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-        (
-            "RemovePytestCov",
-            (AQuery {
-                name: "pytest-cov in nativeCheckInputs".to_string(),
-                solution: "remove this from nativeCheckInputs or change to pytest-cov-stub".to_string(),
-                context: String::new(),
-                what: r"pytest-cov".to_string(),
-                in_what: "nativeCheckInputs".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Remove,
-            })
-        ),
-        (
-            "PytestBenchmarkInNativeCheckInputs",
-            (AQuery {
-                name: "pytest-benchmark in nativeCheckInputs".to_string(),
-                solution: "remove this from nativeCheckInputs or pass `--benchmark-disable` to pytestFlagsArray".to_string(),
-                context: String::new(),
-                what: r"pytest-benchmark".to_string(),
-                in_what: "nativeCheckInputs".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Remove,
-            })
-        ),
-        (
-            "NonFunctionalTestingToolInNativeCheckInputs",
-            (AQuery {
-                name: "non functional testing tool in nativeCheckInputs".to_string(),
-                solution: "remove this from nativeCheckInputs".to_string(),
-                context: String::new(),
-                what: r"pytest-runner|flake8|black|isort|coverage|ruff".to_string(),
-                in_what: "nativeCheckInputs".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Remove,
-            })
-        ),
-        (
-            "DeprecatedTestingToolInNativeCheckInputs",
-            (AQuery {
-                name: "deprecated testing tool in nativeCheckInputs".to_string(),
-                solution: "remove this from nativeCheckInputs".to_string(),
-                context: String::new(),
-                what: r"nose".to_string(),
-                in_what: "nativeCheckInputs".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Remove,
-            })
-        ),
-        (
-            "BarePytestInNativeCheckInputs",
-            (AQuery {
-                name: "bare pytest in nativeCheckInputs".to_string(),
-                solution: "change pytest to pytestCheckHook".to_string(),
-                context: String::new(),
-                what: r"pytest".to_string(),
-                in_what: "nativeCheckInputs".to_string(),
-                type_of_query: QueryType::List,
-                type_of_fix: TypeOfFix::Change,
-            })
-        ),
-    ]);
+fn validated(q: &AQuery) -> AQuery {
+    if let Err(e) = q.validate() {
+        panic!("invalid lint '{}': {e}", q.id);
+    }
+    q.to_owned()
 }
 
 pub fn add_default_queries(queries: &mut Vec<AQuery>) {
-    let mut default_queries = QUERIES.values().cloned().collect();
+    let mut default_queries = QUERIES
+        .values()
+        .filter(|q| q.maturity == Maturity::Stable)
+        .map(validated)
+        .collect();
 
     queries.append(&mut default_queries);
 }
 
+/// Adds the experimental and nursery lints.
 pub fn add_unfinished_queries(queries: &mut Vec<AQuery>) {
-    let mut unfinished_queries = UNFINISHED_QUERIES.values().cloned().collect();
+    let mut unfinished_queries = QUERIES
+        .values()
+        .filter(|q| q.maturity != Maturity::Stable)
+        .map(validated)
+        .collect();
 
     queries.append(&mut unfinished_queries);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{add_unfinished_queries, QUERIES};
+    use crate::find_lints::find_lints;
+    use crate::query::AQuery;
+
+    /// The order of the queries is the order of the matches in reports.
+    #[test]
+    fn queries_sorted_by_id() {
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_unfinished_queries(&mut queries);
+
+        assert!(queries.windows(2).all(|w| w[0].id < w[1].id));
+    }
+
+    /// The ids are used in configs, suppressions and reports, don't rename them.
+    #[test]
+    fn stable_ids() {
+        let mut ids: Vec<&str> = QUERIES.keys().map(String::as_str).collect();
+        ids.sort();
+
+        assert_eq!(
+            ids,
+            [
+                "ArgsToOptionalIsList",
+                "BarePytestInNativeCheckInputs",
+                "BindingWithExpression",
+                "BuildTimeToolInBuildInputs",
+                "DeprecatedFormatAttributeUsage",
+                "DeprecatedFormatOtherAttributeUsage",
+                "DeprecatedSetuptoolsFormat",
+                "DeprecatedTestingToolInNativeCheckInputs",
+                "EndsWithPeriodInDescription",
+                "FlagsNotList",
+                "NoCapitalizationInDescription",
+                "NonFunctionalTestingToolInNativeCheckInputs",
+                "PytestBenchmarkInNativeCheckInputs",
+                "PythonPackageInNativeBuildInputs",
+                "RecKeywordWithMkDerivation",
+                "RedundantPackageFromStdenv",
+                "RedundantPackageInNativeBuildInputs",
+                "RefsTagsWithRev",
+                "RemovePytestCov",
+                "SRIHash",
+                "StartsWithDefiniteOrIndefiniteArticleInDescription",
+                "UnnecessaryWheel",
+                "UnnormalizedPythonPname",
+                "UnusedSuppression",
+                "VersionedPackageInDependencies",
+            ]
+        )
+    }
+
+    /// The examples are shown by `explain`, make sure they're right.
+    #[test]
+    fn examples() {
+        let queries: Vec<_> = QUERIES.values().cloned().collect();
+        let rules = |expr: &str| -> Vec<String> {
+            find_lints("", expr, &queries, &false)
+                .into_iter()
+                .map(|m| m.rule)
+                .collect()
+        };
+
+        for q in QUERIES.values() {
+            assert!(
+                rules(&q.example.bad).contains(&q.id),
+                "{} doesn't match its bad example",
+                q.id
+            );
+            assert!(
+                !rules(&q.example.good).contains(&q.id),
+                "{} matches its good example",
+                q.id
+            );
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use predicates::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::hash::sha256_to_sri;
+
 fn pred(s: &str) -> predicates::str::RegexPredicate {
     predicate::str::is_match(format!("^({s})$")).unwrap()
 }
@@ -16,9 +19,11 @@ pub enum QueryType {
     AttrNameInFunction,
     BindingWithExpression,
     AttrValueInContext,
+    /// `# nixpkgs-lint-disable` comments which silence nothing, found by find_lints
+    UnusedSuppression,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, JsonSchema)]
 pub enum TypeOfFix {
     Remove,
     Move,
@@ -26,20 +31,236 @@ pub enum TypeOfFix {
     ConvertToList,
 }
 
+/// Replaces the text of the capture `@{capture}` of a match with `template`.
+///
+/// In the template `{x}` is the text of the capture `@x`, `{x#prefix}` is the same with
+/// `prefix` removed from its start and `{x|sri}` converts a sha256 string to an SRI hash.
+/// `{{` and `}}` are literal braces.
+/// examples: `{ capture: "a", template: "tag" }`, `{ capture: "q", template: "{q#refs/tags/}" }`
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Rewrite {
+    pub capture: String,
+    pub template: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum TemplateFilter<'a> {
+    StripPrefix(&'a str),
+    Sri,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum TemplatePart<'a> {
+    Text(&'a str),
+    Capture {
+        name: &'a str,
+        filter: Option<TemplateFilter<'a>>,
+    },
+}
+
+impl Rewrite {
+    fn parts(&self) -> Result<Vec<TemplatePart<'_>>, String> {
+        let mut parts = Vec::new();
+        let mut rest = self.template.as_str();
+
+        while !rest.is_empty() {
+            if let Some(r) = rest.strip_prefix("{{") {
+                parts.push(TemplatePart::Text("{"));
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("}}") {
+                parts.push(TemplatePart::Text("}"));
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('{') {
+                let end = r
+                    .find('}')
+                    .ok_or(format!("unclosed '{{' in template '{}'", self.template))?;
+                let capture = &r[..end];
+                let (name, filter) = match capture.find(['#', '|']) {
+                    Some(i) if capture[i..].starts_with('#') => (
+                        &capture[..i],
+                        Some(TemplateFilter::StripPrefix(&capture[i + 1..])),
+                    ),
+                    Some(i) if &capture[i + 1..] == "sri" => {
+                        (&capture[..i], Some(TemplateFilter::Sri))
+                    }
+                    Some(i) => {
+                        return Err(format!("unknown filter '{}'", &capture[i + 1..]));
+                    }
+                    None => (capture, None),
+                };
+                parts.push(TemplatePart::Capture { name, filter });
+                rest = &r[end + 1..];
+            } else if rest.starts_with('}') {
+                return Err(format!("unopened '}}' in template '{}'", self.template));
+            } else {
+                let end = rest.find(['{', '}']).unwrap_or(rest.len());
+                parts.push(TemplatePart::Text(&rest[..end]));
+                rest = &rest[end..];
+            }
+        }
+
+        Ok(parts)
+    }
+
+    /// Fills in the template with `capture_text` which returns the text of a capture by name.
+    pub fn render<'t>(
+        &self,
+        capture_text: impl Fn(&str) -> Option<&'t str>,
+    ) -> Result<String, String> {
+        let mut rendered = String::new();
+
+        for part in self.parts()? {
+            match part {
+                TemplatePart::Text(text) => rendered.push_str(text),
+                TemplatePart::Capture { name, filter } => {
+                    let text = capture_text(name).ok_or(format!("@{name} wasn't captured"))?;
+                    match filter {
+                        Some(TemplateFilter::StripPrefix(prefix)) => rendered.push_str(
+                            text.strip_prefix(prefix)
+                                .ok_or(format!("@{name} doesn't start with '{prefix}'"))?,
+                        ),
+                        Some(TemplateFilter::Sri) => rendered.push_str(&sha256_to_sri(text)?),
+                        None => rendered.push_str(text),
+                    }
+                }
+            }
+        }
+
+        Ok(rendered)
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    clap::ValueEnum,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Hint,
+    Info,
+    Warning,
+    Error,
+}
+
+/// Whether a run with matches of `severities` fails, because of a match of `fail_on` or a more
+/// severe one or because there are more warnings than `max_warnings`.
+pub fn run_fails(severities: &[Severity], fail_on: Severity, max_warnings: Option<usize>) -> bool {
+    let warnings = severities
+        .iter()
+        .filter(|s| **s == Severity::Warning)
+        .count();
+    severities.iter().any(|s| *s >= fail_on) || max_warnings.is_some_and(|max| warnings > max)
+}
+
+/// What a lint is about, used to select lints with `--select python`
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, clap::ValueEnum, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    /// lints about nixpkgs-lint itself or the nix language
+    General,
+    Stdenv,
+    Meta,
+    Fetchers,
+    Python,
+}
+
+/// How far a lint is from being enabled by default
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    clap::ValueEnum,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Maturity {
+    /// enabled by default
+    Stable,
+    /// works, but hasn't been fixed in nixpkgs yet
+    Experimental,
+    /// has known false positives or no fix
+    Nursery,
+}
+
+/// Nix code before and after the fix of a lint
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Example {
+    pub bad: String,
+    pub good: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct AQuery {
+    /// stable key of the lint, e.g. in the config file and suppressions.
+    /// Renaming it breaks users, see the `stable_ids` test.
+    pub id: String,
+    pub category: Category,
+    pub maturity: Maturity,
+    /// the version which added the lint
+    pub since: String,
     pub name: String,
     pub solution: String,
+    /// why the lint exists, shown by `explain`
+    pub explanation: String,
+    pub example: Example,
     /// a regex pattern.
     /// examples: "pkg-config", "cmake|makeWrapper"
     pub what: String,
     pub in_what: String,
+    /// the binding next to `in_what` which TypeOfFix::Move moves matches to
+    pub destination: String,
     pub context: String,
     pub type_of_query: QueryType,
     pub type_of_fix: TypeOfFix,
+    pub severity: Severity,
+    /// mechanical fix for TypeOfFix::Change
+    pub rewrites: Vec<Rewrite>,
 }
 
 impl AQuery {
+    /// Checks that the query compiles and that the rewrites only use its captures.
+    pub fn validate(&self) -> Result<(), String> {
+        let query = tree_sitter::Query::new(tree_sitter_nix::language(), &self.query_string())
+            .map_err(|e| format!("invalid query: {e}"))?;
+        let is_capture = |name: &str| query.capture_index_for_name(name).is_some();
+
+        if !is_capture("q") {
+            return Err(String::from("the query doesn't capture @q"));
+        }
+
+        for rewrite in &self.rewrites {
+            if !is_capture(&rewrite.capture) {
+                return Err(format!("rewrite of unknown capture @{}", rewrite.capture));
+            }
+            for part in rewrite.parts()? {
+                if let TemplatePart::Capture { name, .. } = part {
+                    if !is_capture(name) {
+                        return Err(format!("template uses unknown capture @{name}"));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn query_string(&self) -> String {
         match self.type_of_query {
             QueryType::List => format!(
@@ -76,7 +297,7 @@ impl AQuery {
                             )
                         argument: (list_expression) @l
                     )
-                    (match? @a \"^optional$\")
+                    (#match? @a \"^(lists[.])?optional$\")
                 ) @q",
             ),
             QueryType::XInFormals => format!(
@@ -116,7 +337,7 @@ impl AQuery {
             QueryType::AttrNameInFunction => format!(
                 "(
                     (apply_expression function: _ @f
-                        argument: (_ (_ (binding attrpath: _ @q)))
+                        argument: (_ (_ (binding attrpath: _ @q expression: _ @v)))
                     )
                     (#match? @f \"{}\")
                     (#eq? @q \"{}\")
@@ -137,6 +358,12 @@ impl AQuery {
                 )",
                 self.context, self.in_what, self.what,
             ),
+            QueryType::UnusedSuppression => String::from(
+                "(
+                    (comment) @q
+                    (#match? @q \"nixpkgs-lint-disable\")
+                )",
+            ),
         }
     }
     pub fn what_to_pred(&self) -> predicates::str::RegexPredicate {
@@ -144,23 +371,114 @@ impl AQuery {
     }
 }
 
-#[derive(Serialize, Debug, Eq, PartialEq)]
+/// A match of a lint, lines and columns start at 1
+#[derive(Serialize, Debug, Eq, PartialEq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AMatch {
+    /// the id of the lint
+    pub rule: String,
+    /// the path as it was found from the arguments
     pub file: String,
     pub message: String,
+    /// the text of the match
     pub matched: String,
     pub fix: String,
     pub type_of_fix: TypeOfFix,
+    pub severity: Severity,
     pub line: usize,
-    // end_line is not yet used for anything because all matches will be on 1 line
-    //end_line: usize,
+    pub end_line: usize,
     pub column: usize,
+    /// the column after the match on `endLine`
     pub end_column: usize,
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub byte_range: std::ops::Range<usize>,
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub list_byte_range: std::ops::Range<usize>,
-    #[serde(skip_serializing)]
+    /// the byte ranges of the query's captures by name, used by rewrites
+    #[serde(skip)]
+    pub captures: Vec<(String, std::ops::Range<usize>)>,
+    #[serde(skip)]
     pub query: AQuery,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        run_fails, AQuery, Category, Example, Maturity, QueryType, Rewrite, Severity, TypeOfFix,
+    };
+
+    fn rewrite(capture: &str, template: &str) -> Rewrite {
+        Rewrite {
+            capture: capture.to_string(),
+            template: template.to_string(),
+        }
+    }
+
+    #[test]
+    fn rewrite_render() {
+        let capture_text = |name: &str| match name {
+            "a" => Some("rev"),
+            "q" => Some("refs/tags/v1.0"),
+            _ => None,
+        };
+
+        assert_eq!(
+            rewrite("a", "tag").render(capture_text),
+            Ok("tag".to_string())
+        );
+        assert_eq!(
+            rewrite("q", "{{ {a} = {q#refs/tags/} }}").render(capture_text),
+            Ok("{ rev = v1.0 }".to_string())
+        );
+        assert!(rewrite("q", "{q#v}").render(capture_text).is_err());
+        assert!(rewrite("q", "{q|nope}").render(capture_text).is_err());
+        assert!(rewrite("q", "{q").render(capture_text).is_err());
+    }
+
+    #[test]
+    fn validate_rewrites() {
+        let mut query = AQuery {
+            id: "SRIHash".to_string(),
+            category: Category::Fetchers,
+            maturity: Maturity::Experimental,
+            since: "0.3.0".to_string(),
+            name: "SRI hash".to_string(),
+            solution: "replace this with hash".to_string(),
+            explanation: String::new(),
+            example: Example {
+                bad: String::new(),
+                good: String::new(),
+            },
+            context: String::new(),
+            what: "sha256".to_string(),
+            in_what: "fetchFromGitHub".to_string(),
+            destination: String::new(),
+            type_of_query: QueryType::AttrNameInFunction,
+            type_of_fix: TypeOfFix::Change,
+            severity: Severity::Warning,
+            rewrites: vec![rewrite("q", "hash")],
+        };
+        assert_eq!(query.validate(), Ok(()));
+
+        query.rewrites = vec![rewrite("q", "{l}")];
+        assert!(query.validate().is_err());
+
+        query.rewrites = vec![rewrite("l", "hash")];
+        assert!(query.validate().is_err());
+    }
+
+    #[test]
+    fn fail_policy() {
+        use Severity::*;
+
+        assert!(!run_fails(&[], Hint, None));
+        assert!(run_fails(&[Hint], Hint, None));
+        assert!(!run_fails(&[Hint, Info, Warning], Error, None));
+        assert!(run_fails(&[Warning, Error], Error, None));
+        assert!(run_fails(&[Info, Warning], Warning, Some(10)));
+
+        assert!(!run_fails(&[Warning, Warning, Info], Error, Some(2)));
+        assert!(run_fails(&[Warning, Warning, Warning], Error, Some(2)));
+        assert!(run_fails(&[Warning], Error, Some(0)));
+    }
+}
@@ -0,0 +1,229 @@
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::query::{AQuery, Category, Example, Maturity, Severity, TypeOfFix};
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+pub enum RulesFormat {
+    Table,
+    Json,
+}
+
+/// The documentation of a lint
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleInfo<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub category: Category,
+    pub maturity: Maturity,
+    pub severity: Severity,
+    pub type_of_fix: &'a TypeOfFix,
+    pub since: &'a str,
+    pub solution: &'a str,
+    pub explanation: &'a str,
+    pub example: &'a Example,
+}
+
+impl<'a> From<&'a AQuery> for RuleInfo<'a> {
+    fn from(q: &'a AQuery) -> Self {
+        RuleInfo {
+            id: &q.id,
+            name: &q.name,
+            category: q.category,
+            maturity: q.maturity,
+            severity: q.severity,
+            type_of_fix: &q.type_of_fix,
+            since: &q.since,
+            solution: &q.solution,
+            explanation: &q.explanation,
+            example: &q.example,
+        }
+    }
+}
+
+/// The name of `value` on the command line, e.g. `warning`
+pub fn value_name(value: impl ValueEnum) -> String {
+    value.to_possible_value().unwrap().get_name().to_owned()
+}
+
+/// Writes the lints sorted by maturity and id.
+pub fn write_rules(
+    out: &mut impl Write,
+    format: &RulesFormat,
+    rules: &[&AQuery],
+) -> io::Result<()> {
+    let mut rules = rules.to_vec();
+    rules.sort_by_key(|q| (q.maturity, &q.id));
+
+    match format {
+        RulesFormat::Json => {
+            let rules: Vec<RuleInfo> = rules.into_iter().map(RuleInfo::from).collect();
+            writeln!(out, "{}", serde_json::to_string_pretty(&rules).unwrap())?;
+        }
+        RulesFormat::Table => {
+            let rows: Vec<[String; 5]> = rules
+                .iter()
+                .map(|q| {
+                    [
+                        q.id.to_owned(),
+                        value_name(q.category),
+                        value_name(q.maturity),
+                        value_name(q.severity),
+                        format!("{:?}", q.type_of_fix),
+                    ]
+                })
+                .collect();
+            let header = ["ID", "CATEGORY", "MATURITY", "SEVERITY", "FIX"].map(String::from);
+
+            let mut widths = [0; 5];
+            for row in rows.iter().chain([&header]) {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.len());
+                }
+            }
+
+            for row in [&header].into_iter().chain(&rows) {
+                let line: Vec<String> = row
+                    .iter()
+                    .zip(widths)
+                    .map(|(cell, width)| format!("{cell:width$}"))
+                    .collect();
+                writeln!(out, "{}", line.join("  ").trim_end())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes why the lint exists, its examples and the tree-sitter query which finds it.
+pub fn write_explanation(out: &mut impl Write, q: &AQuery) -> io::Result<()> {
+    let indent = |code: &str| -> String {
+        code.lines()
+            .map(|line| format!("    {line}\n"))
+            .collect::<String>()
+    };
+
+    writeln!(out, "{}: {}", q.id, q.name)?;
+    writeln!(
+        out,
+        "category: {}, maturity: {}, default severity: {}, since: {}",
+        value_name(q.category),
+        value_name(q.maturity),
+        value_name(q.severity),
+        q.since
+    )?;
+    writeln!(out, "fix ({:?}): {}", q.type_of_fix, q.solution)?;
+    writeln!(out)?;
+    writeln!(out, "{}", q.explanation)?;
+    writeln!(out)?;
+    writeln!(out, "bad:")?;
+    writeln!(out, "{}", indent(&q.example.bad))?;
+    writeln!(out, "good:")?;
+    writeln!(out, "{}", indent(&q.example.good))?;
+    // the query is indented for queries.rs
+    let query = q.query_string();
+    let dedent = query
+        .lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    writeln!(out, "query:")?;
+    for (i, line) in query.lines().enumerate() {
+        writeln!(
+            out,
+            "{}",
+            if i == 0 {
+                line
+            } else {
+                line.get(dedent..).unwrap_or("")
+            }
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::{write_explanation, write_rules, RulesFormat};
+    use crate::queries::QUERIES;
+    use crate::query::AQuery;
+
+    fn rules(format: RulesFormat) -> String {
+        let rules: Vec<&AQuery> = QUERIES.values().collect();
+        let mut out = Vec::new();
+        write_rules(&mut out, &format, &rules).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn rules_table() {
+        let table = rules(RulesFormat::Table);
+        let mut lines = table.lines();
+
+        assert_eq!(
+            lines.next().unwrap().split_whitespace().collect::<Vec<_>>(),
+            ["ID", "CATEGORY", "MATURITY", "SEVERITY", "FIX"]
+        );
+        assert_eq!(
+            lines.next().unwrap().split_whitespace().collect::<Vec<_>>(),
+            [
+                "ArgsToOptionalIsList",
+                "general",
+                "stable",
+                "warning",
+                "Change"
+            ]
+        );
+        assert_eq!(table.lines().count(), QUERIES.len() + 1);
+        // the columns are aligned
+        let header = table.lines().next().unwrap();
+        let row = table.lines().nth(1).unwrap();
+        assert_eq!(header.find("CATEGORY"), row.find("general"));
+        assert_eq!(header.find("FIX"), row.find("Change"));
+    }
+
+    #[test]
+    fn rules_json() {
+        let rules: Value = serde_json::from_str(&rules(RulesFormat::Json)).unwrap();
+        let rule = &rules[0];
+
+        assert_eq!(rules.as_array().unwrap().len(), QUERIES.len());
+        assert_eq!(rule["id"], "ArgsToOptionalIsList");
+        assert_eq!(rule["category"], "general");
+        assert_eq!(rule["maturity"], "stable");
+        assert_eq!(rule["typeOfFix"], "Change");
+        assert_eq!(rule["solution"], QUERIES["ArgsToOptionalIsList"].solution);
+    }
+
+    #[test]
+    fn explanation() {
+        let q = &QUERIES["FlagsNotList"];
+        let mut out = Vec::new();
+        write_explanation(&mut out, q).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.starts_with(
+            "FlagsNotList: *Flags not a list\n\
+             category: stdenv, maturity: stable, default severity: warning, since: 0.3.0\n\
+             fix (ConvertToList): convert to a list\n"
+        ));
+        assert!(out.contains(&format!("bad:\n    {}\n", q.example.bad)));
+        assert!(out.contains(&format!("good:\n    {}\n", q.example.good)));
+        // dedented from queries.rs
+        assert!(out.ends_with(
+            "query:
+(
+    (binding attrpath: _ @a expression: (string_expression) @l)
+    (#match? @a \"Flags\")
+) @q
+"
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+use clap::ValueEnum;
+use globset::{Glob, GlobMatcher};
+
+use crate::queries::QUERIES;
+use crate::query::{AQuery, Category, Maturity};
+
+#[derive(Debug)]
+enum Selector {
+    Category(Category),
+    Maturity(Maturity),
+    /// experimental and nursery lints
+    Unfinished,
+    /// a lint id, overrides `enabled = false` of the config
+    Id(String),
+    /// a glob of lint ids, e.g. `Deprecated*`
+    Glob(GlobMatcher),
+}
+
+impl Selector {
+    fn parse(s: &str) -> Result<Selector, String> {
+        if let Ok(category) = Category::from_str(s, true) {
+            return Ok(Selector::Category(category));
+        }
+        if let Ok(maturity) = Maturity::from_str(s, true) {
+            return Ok(Selector::Maturity(maturity));
+        }
+        if s == "unfinished" {
+            return Ok(Selector::Unfinished);
+        }
+        if QUERIES.contains_key(s) {
+            return Ok(Selector::Id(s.to_owned()));
+        }
+
+        let glob = Glob::new(s)
+            .map_err(|e| format!("invalid selector '{s}': {e}"))?
+            .compile_matcher();
+        if !QUERIES.keys().any(|id| glob.is_match(id)) {
+            return Err(format!(
+                "'{s}' isn't a lint id, glob of lint ids, category or maturity"
+            ));
+        }
+        Ok(Selector::Glob(glob))
+    }
+
+    fn matches(&self, q: &AQuery) -> bool {
+        match self {
+            Selector::Category(category) => q.category == *category,
+            Selector::Maturity(maturity) => q.maturity == *maturity,
+            Selector::Unfinished => q.maturity != Maturity::Stable,
+            Selector::Id(id) => q.id == *id,
+            Selector::Glob(glob) => glob.is_match(&q.id),
+        }
+    }
+}
+
+/// The lints chosen by `--select` and `--ignore`.
+#[derive(Debug, Default)]
+pub struct Selection {
+    select: Vec<Selector>,
+    ignore: Vec<Selector>,
+}
+
+impl Selection {
+    pub fn new(select: &[String], ignore: &[String]) -> Result<Selection, String> {
+        Ok(Selection {
+            select: select
+                .iter()
+                .map(|s| Selector::parse(s))
+                .collect::<Result<_, _>>()?,
+            ignore: ignore
+                .iter()
+                .map(|s| Selector::parse(s))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Whether `q` runs, `enabled` is the choice of the config and `default` is used without
+    /// one when nothing is selected. Selected lints run even if they're unfinished, but lints
+    /// disabled in the config only run when they're selected by id.
+    pub fn is_selected(&self, q: &AQuery, enabled: Option<bool>, default: bool) -> bool {
+        let selected = if self.select.is_empty() {
+            enabled.unwrap_or(default)
+        } else {
+            self.select.iter().any(|s| match s {
+                Selector::Id(_) => s.matches(q),
+                _ => s.matches(q) && enabled != Some(false),
+            })
+        };
+        selected && !self.ignore.iter().any(|s| s.matches(q))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Selection;
+    use crate::queries::{add_default_queries, add_unfinished_queries};
+    use crate::query::AQuery;
+
+    fn selected(select: &[&str], ignore: &[&str]) -> Vec<String> {
+        let to_strings = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let selection = Selection::new(&to_strings(select), &to_strings(ignore)).unwrap();
+
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_default_queries(&mut queries);
+        add_unfinished_queries(&mut queries);
+
+        let mut ids: Vec<String> = queries
+            .into_iter()
+            .filter(|q| selection.is_selected(q, None, false))
+            .map(|q| q.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn select_and_ignore() {
+        assert_eq!(selected(&["fetchers"], &[]), ["RefsTagsWithRev", "SRIHash"]);
+        assert_eq!(
+            selected(&["DeprecatedFormat*", "SRIHash"], &["*Other*"]),
+            ["DeprecatedFormatAttributeUsage", "SRIHash"]
+        );
+        assert_eq!(
+            selected(&["stable"], &["general"]),
+            [
+                "BuildTimeToolInBuildInputs",
+                "FlagsNotList",
+                "UnnormalizedPythonPname"
+            ]
+        );
+        assert!(selected(&[], &["python"]).is_empty());
+    }
+
+    #[test]
+    fn select_disabled_in_config() {
+        let selection = |select: &str| Selection::new(&[select.to_string()], &[]).unwrap();
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_unfinished_queries(&mut queries);
+        let q = queries.iter().find(|q| q.id == "SRIHash").unwrap();
+
+        assert!(selection("SRIHash").is_selected(q, Some(false), false));
+        assert!(!selection("fetchers").is_selected(q, Some(false), false));
+        assert!(!selection("SRI*").is_selected(q, Some(false), false));
+        assert!(selection("fetchers").is_selected(q, None, false));
+    }
+
+    #[test]
+    fn unknown_selector() {
+        assert!(Selection::new(&["Typo".to_string()], &[]).is_err());
+        assert!(Selection::new(&[], &["Typo*".to_string()]).is_err());
+    }
+}
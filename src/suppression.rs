@@ -0,0 +1,296 @@
+use std::ops::Range;
+
+use tree_sitter::{Node, Tree};
+
+use crate::queries::QUERIES;
+use crate::query::{AMatch, AQuery, TypeOfFix};
+
+const DISABLE_NEXT_LINE: &str = "nixpkgs-lint-disable-next-line";
+const DISABLE: &str = "nixpkgs-lint-disable";
+
+#[derive(Debug, Eq, PartialEq)]
+enum Scope {
+    /// the 1-based line after the comment
+    NextLine(usize),
+    File,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct SuppressedId {
+    id: String,
+    byte_range: Range<usize>,
+    used: bool,
+}
+
+/// A `# nixpkgs-lint-disable-next-line Id, OtherId -- reason` or
+/// `# nixpkgs-lint-disable Id` comment, without ids it disables every lint.
+#[derive(Debug)]
+pub struct Suppression<'t> {
+    comment: Node<'t>,
+    scope: Scope,
+    ids: Vec<SuppressedId>,
+    used: bool,
+}
+
+impl Suppression<'_> {
+    fn covers(&self, m: &AMatch) -> bool {
+        match self.scope {
+            Scope::NextLine(line) => m.line == line,
+            Scope::File => true,
+        }
+    }
+
+    /// Marks the suppression as used if it silences `m`.
+    pub fn suppresses(&mut self, m: &AMatch) -> bool {
+        if !self.covers(m) {
+            return false;
+        }
+
+        if self.ids.is_empty() {
+            self.used = true;
+            return true;
+        }
+
+        match self.ids.iter_mut().find(|s| s.id == m.rule) {
+            Some(s) => {
+                s.used = true;
+                self.used = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reports the ids which silenced nothing and the ids which aren't lints at all. Lints
+    /// missing from `queries` are skipped as they may be disabled in this run.
+    pub fn unused(&self, path: &str, text: &str, queries: &[AQuery], q: &AQuery) -> Vec<AMatch> {
+        let to_match = |byte_range: Range<usize>, message: String, fix: String, type_of_fix| {
+            let line_start = text[..byte_range.start].rfind('\n').map_or(0, |i| i + 1);
+            let column = byte_range.start - line_start;
+            AMatch {
+                rule: q.id.to_owned(),
+                file: path.to_owned(),
+                message,
+                matched: text[byte_range.clone()].to_owned(),
+                fix,
+                type_of_fix,
+                severity: q.severity,
+                line: text[..byte_range.start].matches('\n').count() + 1,
+                end_line: text[..byte_range.end].matches('\n').count() + 1,
+                column: column + 1,
+                end_column: column + byte_range.len() + 1,
+                byte_range,
+                list_byte_range: 0..0,
+                captures: vec![],
+                query: q.to_owned(),
+            }
+        };
+
+        let unused_match = |byte_range: Range<usize>| {
+            to_match(
+                byte_range,
+                q.name.to_owned(),
+                q.solution.to_owned(),
+                q.type_of_fix.to_owned(),
+            )
+        };
+
+        if self.ids.is_empty() {
+            if self.used {
+                return vec![];
+            }
+            return vec![unused_match(self.comment.byte_range())];
+        }
+
+        let unknown: Vec<AMatch> = self
+            .ids
+            .iter()
+            .filter(|s| !QUERIES.contains_key(&s.id))
+            .map(|s| {
+                to_match(
+                    s.byte_range.to_owned(),
+                    format!("`{}` isn't a lint id", s.id),
+                    String::from("fix the id, see `nixpkgs-lint list-rules`"),
+                    // likely a typo, which isn't removed
+                    TypeOfFix::Change,
+                )
+            })
+            .collect();
+        let unused: Vec<&SuppressedId> = self
+            .ids
+            .iter()
+            .filter(|s| !s.used && queries.iter().any(|q| q.id == s.id))
+            .collect();
+
+        if unknown.is_empty() && unused.len() == self.ids.len() {
+            // the whole comment can go
+            return vec![unused_match(self.comment.byte_range())];
+        }
+        let mut matches: Vec<AMatch> = unused
+            .iter()
+            .map(|s| unused_match(s.byte_range.to_owned()))
+            .collect();
+        matches.extend(unknown);
+        matches.sort_by_key(|m| m.byte_range.start);
+        matches
+    }
+}
+
+fn parse_comment<'t>(comment: Node<'t>, text: &str) -> Option<Suppression<'t>> {
+    let comment_text = &text[comment.byte_range()];
+    let content = match comment_text.strip_prefix('#') {
+        Some(content) => content,
+        None => comment_text.strip_prefix("/*")?.strip_suffix("*/")?,
+    };
+    let content = content.trim_start();
+
+    let (scope, rest) = if let Some(rest) = content.strip_prefix(DISABLE_NEXT_LINE) {
+        (Scope::NextLine(comment.end_position().row + 2), rest)
+    } else if let Some(rest) = content.strip_prefix(DISABLE) {
+        (Scope::File, rest)
+    } else {
+        return None;
+    };
+    // e.g. nixpkgs-lint-disable-next-lines
+    if rest.starts_with(|c: char| !c.is_whitespace()) {
+        return None;
+    }
+
+    let ids = rest
+        .split("--")
+        .next()
+        .unwrap()
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            // `id` is a slice of `comment_text`
+            let start =
+                comment.start_byte() + (id.as_ptr() as usize - comment_text.as_ptr() as usize);
+            SuppressedId {
+                id: id.to_owned(),
+                byte_range: start..start + id.len(),
+                used: false,
+            }
+        })
+        .collect();
+
+    Some(Suppression {
+        comment,
+        scope,
+        ids,
+        used: false,
+    })
+}
+
+pub fn find_suppressions<'t>(tree: &'t Tree, text: &str) -> Vec<Suppression<'t>> {
+    let cursor = &mut tree.root_node().walk();
+    tree_sitter_traversal::traverse(cursor, tree_sitter_traversal::Order::Pre)
+        .filter(|n| n.kind() == "comment")
+        .filter_map(|n| parse_comment(n, text))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        find_lints::find_lints,
+        queries::add_default_queries,
+        query::{AQuery, TypeOfFix},
+    };
+
+    fn lint(expr: &str) -> Vec<(String, String)> {
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_default_queries(&mut queries);
+        find_lints("", expr, &queries, &false)
+            .into_iter()
+            .map(|m| (m.rule, m.matched))
+            .collect()
+    }
+
+    #[test]
+    fn suppressions() {
+        let expr = "# nixpkgs-lint-disable FlagsNotList
+            { stdenv, pkg-config, cmake }:
+
+            stdenv.mkDerivation {
+              configureFlags = \"--a --b\";
+              buildInputs = [
+                # nixpkgs-lint-disable-next-line BuildTimeToolInBuildInputs -- needed at runtime
+                pkg-config
+                cmake
+              ];
+            }";
+
+        assert_eq!(
+            lint(expr),
+            [(
+                "BuildTimeToolInBuildInputs".to_string(),
+                "cmake".to_string()
+            )]
+        )
+    }
+
+    #[test]
+    fn unused_suppressions() {
+        let expr = "{ stdenv, pkg-config }:
+
+            stdenv.mkDerivation {
+              buildInputs = [
+                # nixpkgs-lint-disable-next-line BuildTimeToolInBuildInputs, SRIHash
+                zlib
+                # nixpkgs-lint-disable-next-line BuildTimeToolInBuildInputs
+                ninja
+                # nixpkgs-lint-disable-next-line BuildTimeToolInBuildInputs, FlagsNotList
+                pkg-config
+                # nixpkgs-lint-disable-next-line BuildTimeToolInBuildInput
+                cmake
+              ];
+            }";
+
+        assert_eq!(
+            lint(expr),
+            [
+                (
+                    "BuildTimeToolInBuildInputs".to_string(),
+                    "cmake".to_string()
+                ),
+                (
+                    "UnusedSuppression".to_string(),
+                    "BuildTimeToolInBuildInputs".to_string()
+                ),
+                (
+                    "UnusedSuppression".to_string(),
+                    "# nixpkgs-lint-disable-next-line BuildTimeToolInBuildInputs".to_string()
+                ),
+                ("UnusedSuppression".to_string(), "FlagsNotList".to_string()),
+                (
+                    "UnusedSuppression".to_string(),
+                    "BuildTimeToolInBuildInput".to_string()
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn unknown_ids_are_not_removed() {
+        let expr = "{
+              # nixpkgs-lint-disable-next-line BuildTimeToolInBuildInput, FlagsNotList
+              buildInputs = [ cmake ];
+            }";
+        let mut queries: Vec<AQuery> = Vec::new();
+        add_default_queries(&mut queries);
+        let fixes: Vec<(String, TypeOfFix)> = find_lints("", expr, &queries, &false)
+            .into_iter()
+            .filter(|m| m.rule == "UnusedSuppression")
+            .map(|m| (m.matched, m.type_of_fix))
+            .collect();
+
+        assert_eq!(
+            fixes,
+            [
+                ("BuildTimeToolInBuildInput".to_string(), TypeOfFix::Change),
+                ("FlagsNotList".to_string(), TypeOfFix::Remove),
+            ]
+        )
+    }
+}